@@ -11,8 +11,11 @@ use std::{env, io};
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use thiserror::Error;
 
-use superline::config::{Config, TerminalRuntimeMetadata};
-use superline::modules::refresh_pr;
+use superline::config::LineSegment;
+use superline::config::{Config, ForgeTemplate, PrProvider, TerminalRuntimeMetadata};
+use superline::modules::{
+    refresh_current_pr, refresh_hg, refresh_jj, refresh_pr, CachedPr, PrLookup,
+};
 use superline::terminal::{Shell, SHELL};
 use superline::themes::{CustomTheme, RainbowTheme, SimpleTheme};
 use superline::Powerline;
@@ -25,11 +28,13 @@ function __pl_cache_duration --on-event fish_postexec
 end
 
 function fish_prompt
-  superline show -s $status -c $COLUMNS fish $__pl_duration
+  set -l __pl_status $status
+  superline show -s $__pl_status -c $COLUMNS --jobs (count (jobs -p)) --dirs (count $dirstack) fish $__pl_duration
 end
 
 function fish_right_prompt
-  superline show-right -s $status -c $COLUMNS fish $__pl_duration
+  set -l __pl_status $status
+  superline show-right -s $__pl_status -c $COLUMNS --jobs (count (jobs -p)) --dirs (count $dirstack) fish $__pl_duration
 end
 "#;
 
@@ -48,14 +53,16 @@ function preexec() {
 }
 
 function _update_ps1() {
+    local __pl_status=$?
     if [ $__pl_timer ]; then
         _now=$(($(gdate +%s%0N)/1000000))
         if [ $_now -ge $__pl_timer ]; then
             _elapsed=$(($_now-$__pl_timer))
         fi
     fi
-    PS1="$(superline show -s $? -c $COLUMNS zsh $_elapsed)"
-    RPS1="$(superline show-right -s $? -c $COLUMNS zsh $_elapsed)"
+    local __pl_state=(-s $__pl_status -c $COLUMNS --jobs ${(%):-%j} --dirs ${#dirstack})
+    PS1="$(superline show $__pl_state zsh $_elapsed)"
+    RPS1="$(superline show-right $__pl_state zsh $_elapsed)"
     unset __pl_timer _elapsed _now
 }
 
@@ -72,7 +79,9 @@ const BASH_CONF: &str = r#"
 export SUPERLINE_BASH=1
 
 function _update_ps1() {
-    PS1="$(superline show -s $? -c $COLUMNS bash)"
+    local __pl_status=$? __pl_jobs
+    __pl_jobs=$(jobs -p | wc -l)
+    PS1="$(superline show -s $__pl_status -c $COLUMNS --jobs $__pl_jobs --dirs $((${#DIRSTACK[@]} - 1)) bash)"
 }
 
 if [ "$TERM" != "linux" ]; then
@@ -118,7 +127,11 @@ function global:prompt {
     try { $__pl_cols = $Host.UI.RawUI.WindowSize.Width } catch {}
     if (-not $__pl_cols -or $__pl_cols -le 0) { $__pl_cols = 80 }
 
-    $__pl_args = @('show', '-s', $__pl_status, '-c', $__pl_cols, 'pwsh')
+    # Jobs that finished linger in `Get-Job` until removed; count the live ones.
+    $__pl_jobs = @(Get-Job | Where-Object { $_.State -in 'Running', 'Suspended' }).Count
+    $__pl_dirs = (Get-Location -Stack).Count
+
+    $__pl_args = @('show', '-s', $__pl_status, '-c', $__pl_cols, '--jobs', $__pl_jobs, '--dirs', $__pl_dirs, 'pwsh')
 
     # Duration of the last command, in milliseconds, from session history.
     $__pl_last = Get-History -Count 1
@@ -151,10 +164,21 @@ enum PowerlineArgs {
     ShowRight(ShowArgs),
     Install(InstallArgs),
     Config,
+    /// Inspect, refresh and prune the cached PR lookups.
+    #[command(subcommand)]
+    Cache(CacheSubcommand),
     /// Internal: refresh the cached PR lookup for a branch. Spawned in the
     /// background by the `pr` module - not intended to be called by hand.
     #[command(hide = true)]
     RefreshPr(RefreshPrArgs),
+    /// Internal: refresh the cached `jj log` output for a workspace. Spawned in
+    /// the background by the `jj` module - not intended to be called by hand.
+    #[command(hide = true)]
+    RefreshJj(RefreshRepoArgs),
+    /// Internal: refresh the cached incoming/outgoing counts for a Mercurial
+    /// repo. Spawned in the background by the `hg` module.
+    #[command(hide = true)]
+    RefreshHg(RefreshRepoArgs),
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -165,6 +189,23 @@ enum ShellSubcommand {
     Pwsh,
 }
 
+#[derive(Debug, Subcommand)]
+enum CacheSubcommand {
+    /// List the cached PR lookups with their repo, branch, age and state.
+    List,
+    /// Look up the current branch's PR now and wait for the result.
+    Refresh {
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Delete the cached PR lookups. Other segments' caches are kept.
+    Clear {
+        /// Only delete entries at least this old, e.g. `30m`, `12h` or `7d`.
+        #[arg(long, value_parser = parse_age)]
+        older_than: Option<Duration>,
+    },
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum ShellArg {
     Bash,
@@ -194,6 +235,12 @@ struct ShowArgs {
     columns: usize,
     #[arg(short, long)]
     status: String,
+    /// Number of background jobs.
+    #[arg(long, default_value_t = 0)]
+    jobs: usize,
+    /// Depth of the `pushd` directory stack.
+    #[arg(long, default_value_t = 0)]
+    dirs: usize,
     #[arg(long)]
     config: Option<PathBuf>,
 }
@@ -206,6 +253,43 @@ struct RefreshPrArgs {
     repo_dir: PathBuf,
     #[arg(long)]
     cache: PathBuf,
+    #[arg(long, value_enum, default_value = "auto")]
+    provider: ProviderArg,
+    #[arg(long)]
+    api_url: Option<String>,
+    /// A self-hosted forge's host pattern and kind, as `host=kind`.
+    #[arg(long = "forge", value_parser = parse_forge)]
+    forges: Vec<ForgeTemplate>,
+    #[arg(long)]
+    commit: Option<String>,
+    /// Only look up the `--commit` checks, not the branch's PR.
+    #[arg(long)]
+    skip_pr: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum ProviderArg {
+    Auto,
+    Github,
+    Gitlab,
+}
+
+impl From<ProviderArg> for PrProvider {
+    fn from(arg: ProviderArg) -> Self {
+        match arg {
+            ProviderArg::Auto => PrProvider::Auto,
+            ProviderArg::Github => PrProvider::GitHub,
+            ProviderArg::Gitlab => PrProvider::GitLab,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct RefreshRepoArgs {
+    #[arg(long)]
+    repo_dir: PathBuf,
+    #[arg(long)]
+    cache: PathBuf,
 }
 
 #[derive(Debug, Args)]
@@ -232,6 +316,14 @@ impl TerminalRuntimeMetadata for &ShowArgs {
     fn last_command_status(&self) -> &str {
         self.status.as_str()
     }
+
+    fn background_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    fn directory_stack_depth(&self) -> usize {
+        self.dirs
+    }
 }
 
 fn main() {
@@ -243,7 +335,21 @@ fn main() {
         PowerlineArgs::ShowRight(args) => show(args, true),
         PowerlineArgs::Install(args) => install(args),
         PowerlineArgs::Config => open_config(),
-        PowerlineArgs::RefreshPr(args) => refresh_pr(&args.branch, &args.repo_dir, &args.cache),
+        PowerlineArgs::Cache(command) => manage_cache(command),
+        PowerlineArgs::RefreshPr(args) => refresh_pr(
+            &args.branch,
+            &args.repo_dir,
+            &args.cache,
+            &PrLookup {
+                provider: args.provider.into(),
+                api_url: args.api_url.as_deref(),
+                forges: &args.forges,
+            },
+            args.commit.as_deref(),
+            args.skip_pr,
+        ),
+        PowerlineArgs::RefreshJj(args) => refresh_jj(&args.repo_dir, &args.cache),
+        PowerlineArgs::RefreshHg(args) => refresh_hg(&args.repo_dir, &args.cache),
     }
 }
 
@@ -398,6 +504,129 @@ fn open_config() {
         .expect("Failed to get editor exit status");
 }
 
+fn manage_cache(command: CacheSubcommand) {
+    match command {
+        CacheSubcommand::List => print_cached_prs(&CachedPr::all()),
+        CacheSubcommand::Refresh { config } => {
+            // Look up the PR the way the prompt's `pr` segment is set up to.
+            let conf = match load_config(config) {
+                Ok((conf, _)) => conf,
+                Err(e) => {
+                    eprintln!("superline error: {}", e);
+                    return;
+                }
+            };
+            let segment = conf.rows.iter().find_map(|row| {
+                row.segments().find_map(|segment| match segment {
+                    LineSegment::Pr {
+                        provider,
+                        api_url,
+                        skip_branches,
+                        ci,
+                        ..
+                    } => Some((row, *provider, api_url.as_deref(), skip_branches, *ci)),
+                    _ => None,
+                })
+            });
+            let Some((row, provider, api_url, skip_branches, ci)) = segment else {
+                eprintln!("superline: the config has no pr segment");
+                return;
+            };
+
+            let forges = row.forges();
+            let lookup = PrLookup {
+                provider,
+                api_url,
+                forges: &forges,
+            };
+            match refresh_current_pr(&lookup, skip_branches, ci) {
+                Some(cached) => print_cached_prs(&[cached]),
+                None => eprintln!("superline: not on a branch of a git repository"),
+            }
+        }
+        CacheSubcommand::Clear { older_than } => {
+            let removed = superline::cache::clear("pr", older_than);
+            println!("removed {} cache files", removed);
+        }
+    }
+}
+
+fn print_cached_prs(cached: &[CachedPr]) {
+    let repos: Vec<String> = cached
+        .iter()
+        .map(|c| match &c.repo {
+            Some(repo) => repo.display().to_string(),
+            None => c.path.display().to_string(),
+        })
+        .collect();
+    let repo_width = repos.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    let branch_width = cached
+        .iter()
+        .map(|c| c.branch.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for (c, repo) in cached.iter().zip(&repos) {
+        println!(
+            "{:<repo_width$}  {:<branch_width$}  {:>4}  {}",
+            repo,
+            c.branch,
+            format_age(c.age),
+            c.state
+        );
+    }
+}
+
+/// Parses `--older-than`: a number of seconds, optionally suffixed with `s`,
+/// `m`, `h` or `d`.
+fn parse_age(age: &str) -> Result<Duration, String> {
+    let (number, unit) = match age.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => age.split_at(i),
+        None => (age, "s"),
+    };
+    let number: u64 = number.parse().map_err(|_| {
+        format!(
+            "`{}` isn't a number of seconds, minutes, hours or days",
+            age
+        )
+    })?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("unknown unit `{}`: use s, m, h or d", unit)),
+    };
+    number
+        .checked_mul(unit)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("`{}` is too long an age", age))
+}
+
+/// A `--forge` argument: a host pattern and the forge's kind, as `host=kind`.
+fn parse_forge(forge: &str) -> Result<ForgeTemplate, String> {
+    let (host, kind) = forge
+        .rsplit_once('=')
+        .ok_or_else(|| format!("`{}` isn't of the form host=kind", forge))?;
+    Ok(ForgeTemplate {
+        host: host.to_string(),
+        kind: Some(kind.parse()?),
+        branch_url: None,
+        compare_url: None,
+        commit_url: None,
+    })
+}
+
+/// An age in its largest whole unit, e.g. `42s`, `5m` or `3d`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
 fn print_shell_conf(shell: ShellSubcommand) {
     match shell {
         ShellSubcommand::Bash => println!("{}", BASH_CONF),
@@ -577,6 +806,17 @@ mod tests {
         assert!(invoked_from_powershell_core(Some(core)));
     }
 
+    #[test]
+    fn parses_cache_ages() {
+        assert_eq!(parse_age("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_age("7d"), Ok(Duration::from_secs(7 * 24 * 60 * 60)));
+        assert!(parse_age("1w").is_err());
+        assert!(parse_age("h").is_err());
+        assert!(parse_age(&format!("{}d", u64::MAX / 2)).is_err());
+        assert_eq!(format_age(3 * 3600 + 5), "3h");
+    }
+
     #[test]
     fn install_detection_matches_each_shell_snippet() {
         // The real install snippets must be recognised by the idempotency check.
@@ -0,0 +1,187 @@
+//! The on-disk cache behind segments whose data is slow to get (a network
+//! request, or a VCS binary). Each entry is a JSON file under
+//! `cache_dir()/superline`, refreshed by a detached copy of this binary so the
+//! prompt never waits on it. A `.lock` file next to the entry debounces those
+//! refreshes, and writes go through a `.tmp` file plus a rename so a reader
+//! never sees a half-written entry.
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Where every entry lives. `None` when the platform has no cache directory.
+pub fn dir() -> Option<PathBuf> {
+    crate::platform::cache_dir().map(|base| base.join("superline"))
+}
+
+/// The cache file for `key`, e.g. `pr-<hash>.json` for a `"pr"` prefix. `None`
+/// when there's no cache directory to put it in.
+pub fn cache_path(prefix: &str, key: impl Hash) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    Some(dir()?.join(format!("{}-{:016x}.json", prefix, hasher.finish())))
+}
+
+/// The entries whose file name starts with `prefix`, e.g. every `pr-*.json`.
+pub fn entries(prefix: &str) -> Vec<PathBuf> {
+    let Some(Ok(dir)) = dir().map(fs::read_dir) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}-", prefix);
+    dir.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .collect()
+}
+
+/// Deletes the entries whose file name starts with `prefix`, e.g. every
+/// `pr-*` one, along with their `.lock` and any `.tmp` file left behind by an
+/// interrupted write - or only the files last written more than `older_than`
+/// ago. Other segments' entries are left alone. Returns how many files went.
+pub fn clear(prefix: &str, older_than: Option<Duration>) -> usize {
+    dir().map_or(0, |dir| clear_in(&dir, prefix, older_than))
+}
+
+fn clear_in(dir: &Path, prefix: &str, older_than: Option<Duration>) -> usize {
+    let Ok(dir) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let prefix = format!("{}-", prefix);
+    dir.flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter(|entry| {
+            older_than.is_none_or(|age| {
+                entry
+                    .metadata()
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|elapsed| elapsed >= age)
+            })
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+pub fn read<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    serde_json::from_reader(File::open(path).ok()?).ok()
+}
+
+pub fn write<T: Serialize>(path: &Path, value: &T) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let tmp = path.with_extension("tmp");
+    if let Ok(mut file) = File::create(&tmp) {
+        if serde_json::to_writer(&mut file, value).is_ok() && file.flush().is_ok() {
+            let _ = fs::rename(&tmp, path);
+        }
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn is_stale(fetched_at: u64, ttl: Duration) -> bool {
+    now_secs().saturating_sub(fetched_at) >= ttl.as_secs()
+}
+
+/// True if a refresh was kicked off recently enough that we should let it
+/// finish rather than spawning another one.
+fn refresh_in_flight(lock_path: &Path, debounce: Duration) -> bool {
+    fs::metadata(lock_path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < debounce)
+}
+
+/// Runs this binary with `args` in a detached process to refresh the entry at
+/// `cache_path`, unless another refresh started within `debounce`. The child's
+/// stdio is redirected to null so the shell's command substitution doesn't
+/// block waiting on the inherited pipe.
+pub fn spawn_refresh<I, A>(cache_path: &Path, debounce: Duration, args: I)
+where
+    I: IntoIterator<Item = A>,
+    A: AsRef<OsStr>,
+{
+    let lock_path = cache_path.with_extension("lock");
+    if refresh_in_flight(&lock_path, debounce) {
+        return;
+    }
+
+    if let Some(parent) = cache_path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // Touch the lock up front to debounce concurrent prompts.
+    let _ = File::create(&lock_path);
+
+    let Ok(exe) = env::current_exe() else {
+        return;
+    };
+
+    let _ = Command::new(exe)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+/// Called by the refreshing process once the new entry is written.
+pub fn finish_refresh(cache_path: &Path) {
+    let _ = fs::remove_file(cache_path.with_extension("lock"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clear_in;
+    use crate::utils::TempDir;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn clears_only_the_given_segments_files() {
+        let dir = TempDir::new("cache");
+        for name in [
+            "pr-1.json",
+            "pr-1.lock",
+            "pr-2.tmp",
+            "jj-1.json",
+            "jj-1.lock",
+            "hg-1.json",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(clear_in(&dir, "pr", Some(Duration::from_secs(3600))), 0);
+        assert_eq!(clear_in(&dir, "pr", None), 3);
+        let mut left: Vec<String> = fs::read_dir(&*dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["hg-1.json", "jj-1.json", "jj-1.lock"]);
+    }
+}
@@ -1,12 +1,23 @@
 use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
+use regex::Regex;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 
 pub trait TerminalRuntimeMetadata {
     fn shell_name(&self) -> String;
     fn total_columns(&self) -> usize;
     fn last_command_duration(&self) -> Option<Duration>;
     fn last_command_status(&self) -> &str;
+    /// Jobs running in the background or suspended.
+    fn background_jobs(&self) -> usize {
+        0
+    }
+    /// Directories `pushd` has stacked up, not counting the current one.
+    fn directory_stack_depth(&self) -> usize {
+        0
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,8 +33,31 @@ pub struct CommandLine {
     pub right: Option<Vec<LineSegment>>,
 }
 
+impl CommandLine {
+    /// Every segment on the line, left side first.
+    pub fn segments(&self) -> impl Iterator<Item = &LineSegment> {
+        self.left.iter().chain(self.right.iter().flatten())
+    }
+
+    /// The self-hosted forges configured on the line's `git` and `git_commit`
+    /// segments, which the `pr` segment shares.
+    pub fn forges(&self) -> Vec<ForgeTemplate> {
+        self.segments()
+            .flat_map(|segment| match segment {
+                LineSegment::Git { forges, .. } | LineSegment::GitCommit { forges } => {
+                    forges.as_slice()
+                }
+                _ => &[],
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+// `remote = "Self"` turns the derived impls into inherent functions, so the
+// trait impls below can accept the legacy bare-string forms before delegating.
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case", remote = "Self")]
 pub enum LineSegment {
     SmallSpacer,
     LargeSpacer,
@@ -35,18 +69,193 @@ pub enum LineSegment {
         resolve_symlinks: bool,
     },
     ReadOnly,
-    Git,
+    Git {
+        /// Run a status inside every submodule to show the submodule segment.
+        /// Off by default, as it costs a status walk per submodule.
+        #[serde(default)]
+        submodules: bool,
+        /// Count the repo's other worktrees with uncommitted changes. Off by
+        /// default, as it costs a status walk per worktree.
+        #[serde(default)]
+        worktrees: bool,
+        /// Where the branch segment links to on the remote's web UI.
+        #[serde(default)]
+        link: BranchLink,
+        /// URL layouts for forges that can't be recognised from their host.
+        #[serde(default)]
+        forges: Vec<ForgeTemplate>,
+    },
+    GitCommit {
+        /// URL layouts for forges that can't be recognised from their host.
+        #[serde(default)]
+        forges: Vec<ForgeTemplate>,
+    },
+    GitIdentity {
+        /// Expected `user.email` pattern per remote host. The first rule whose
+        /// `host` matches the repo's default remote is checked.
+        #[serde(default)]
+        rules: Vec<IdentityRule>,
+        /// Hide the segment unless the email breaks the matching rule.
+        #[serde(default)]
+        only_on_mismatch: bool,
+        /// Append an abbreviated `user.signingkey` when one is configured.
+        #[serde(default)]
+        signing_key: bool,
+    },
+    GitTicket {
+        /// Pulls the ticket key out of the branch name: the first capture
+        /// group, or the whole match. Jira-style keys (`PROJ-123`) by default.
+        #[serde(default = "default_ticket_pattern", with = "ticket_pattern")]
+        pattern: Regex,
+        /// Link to the ticket, with `{key}` for the key. `{host}` and `{repo}`
+        /// are filled in from the default remote, for issues kept alongside
+        /// the code.
+        url: String,
+    },
+    Kube {
+        /// Short names for long context names, such as EKS and GKE ones. The
+        /// first alias whose `context` pattern matches is shown.
+        #[serde(default)]
+        aliases: Vec<KubeAlias>,
+        /// Contexts to show in the alert colours, as `*`/`?` patterns matched
+        /// against both the context name and its alias.
+        #[serde(default = "default_production")]
+        production: Vec<String>,
+        /// Append the context's namespace. On by default.
+        #[serde(default = "default_true")]
+        namespace: bool,
+    },
+    Aws {
+        /// Append the profile's region. On by default.
+        #[serde(default = "default_true")]
+        region: bool,
+        /// Append the time left on the profile's credentials, when it's
+        /// known. On by default.
+        #[serde(default = "default_true")]
+        expiry: bool,
+    },
+    Gcloud {
+        /// Append the configuration's account. On by default.
+        #[serde(default = "default_true")]
+        account: bool,
+        /// Projects to show in the alert colours, as `*`/`?` patterns.
+        #[serde(default = "default_production")]
+        production: Vec<String>,
+    },
+    Azure {
+        /// Append the account signed in to the subscription. On by default.
+        #[serde(default = "default_true")]
+        account: bool,
+        /// Subscriptions to show in the alert colours, as `*`/`?` patterns
+        /// matched against their names.
+        #[serde(default = "default_production")]
+        production: Vec<String>,
+    },
+    Terraform {
+        /// Append the `required_version` constraint. Off by default.
+        #[serde(default)]
+        version: bool,
+        /// Workspaces to show in the alert colours, as `*`/`?` patterns.
+        #[serde(default = "default_production")]
+        production: Vec<String>,
+    },
+    Hg {
+        /// Give up on `hg status` after this many milliseconds and show just
+        /// the branch.
+        #[serde(default = "default_hg_timeout")]
+        timeout_ms: u64,
+        /// Look up incoming/outgoing changesets in the background.
+        #[serde(default = "default_true")]
+        remote: bool,
+    },
+    Jj,
     Pr {
         /// Append a coloured dot reflecting the PR's CI check status. On by
         /// default; set to `false` to show just the PR number.
         #[serde(default = "default_true")]
         status: bool,
+        /// Append glyphs for the review decision, unresolved review threads
+        /// and merge conflicts. On by default.
+        #[serde(default = "default_true")]
+        review: bool,
+        /// Break the CI status down into passing, failing and pending counts
+        /// (`✓12 ✗1 ●3`) instead of a single dot.
+        #[serde(default)]
+        check_counts: bool,
+        /// Show the CI status of `HEAD` when the branch has no PR, including on
+        /// `main`, `master` and `develop`. Off by default, as it adds a lookup
+        /// on branches that otherwise never touch the network.
+        #[serde(default)]
+        ci: bool,
+        /// Where to look up the PR. Picked from the remote's host by default.
+        #[serde(default)]
+        provider: PrProvider,
+        /// GitHub GraphQL endpoint, for GitHub Enterprise. Defaults to the one
+        /// matching the remote's host.
+        #[serde(default)]
+        api_url: Option<String>,
+        /// Seconds a lookup stays fresh before it's refreshed in the
+        /// background. Failing lookups back off from this.
+        #[serde(default = "default_pr_cache_ttl")]
+        cache_ttl: u64,
+        /// Seconds to wait before spawning another refresh while one is
+        /// still running.
+        #[serde(default = "default_pr_refresh_debounce")]
+        refresh_debounce: u64,
+        /// Branches with no PR of their own, as glob patterns such as
+        /// `release/*`. Only their CI status is shown.
+        #[serde(default = "default_pr_skip_branches")]
+        skip_branches: Vec<String>,
+        /// Send a terminal notification when the PR's checks finish. Off by
+        /// default.
+        #[serde(default)]
+        notify: Option<NotifyStyle>,
+        /// Ring the bell when the PR's checks finish.
+        #[serde(default)]
+        bell: bool,
     },
     PythonEnv,
     Nvm,
+    Nix {
+        /// Also show whether direnv has loaded the nearest `.envrc`. On by
+        /// default.
+        #[serde(default = "default_true")]
+        direnv: bool,
+    },
     Sdkman,
     Cargo,
     Host,
+    Jobs,
+    Battery {
+        /// Hide the segment while the charge is above this percentage.
+        #[serde(default = "default_battery_hide_above")]
+        hide_above: u8,
+        /// Use the low battery colours at or below this percentage.
+        #[serde(default = "default_battery_warning")]
+        warning: u8,
+        /// Use the critical battery colours at or below this percentage.
+        #[serde(default = "default_battery_critical")]
+        critical: u8,
+    },
+    Container,
+    Docker,
+    Load {
+        /// Show the 1-minute load average once it reaches this. Defaults to
+        /// the number of CPUs.
+        #[serde(default)]
+        above: Option<f64>,
+    },
+    Memory {
+        /// Show the share of memory in use once it reaches this percentage.
+        #[serde(default = "default_memory_above")]
+        above: u8,
+    },
+    Disk {
+        /// Show the space left on the cwd's filesystem once it drops to this
+        /// percentage of its size.
+        #[serde(default = "default_disk_below")]
+        below: u8,
+    },
     Shell,
     Time {
         format: Option<String>,
@@ -59,10 +268,229 @@ pub enum LineSegment {
     Padding(usize),
 }
 
+/// Segments whose options all have defaults, so the bare string form (e.g.
+/// `"git"`, which predates its options) is accepted and means "all options
+/// default".
+const BARE_SEGMENTS_WITH_OPTIONS: &[&str] = &[
+    "git",
+    "git_commit",
+    "git_identity",
+    "hg",
+    "kube",
+    "aws",
+    "gcloud",
+    "azure",
+    "terraform",
+    "nix",
+    "battery",
+    "load",
+    "memory",
+    "disk",
+];
+
+impl<'de> Deserialize<'de> for LineSegment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = match Value::deserialize(deserializer)? {
+            Value::String(name) if BARE_SEGMENTS_WITH_OPTIONS.contains(&name.as_str()) => {
+                Value::Object(
+                    [(name, Value::Object(Default::default()))]
+                        .into_iter()
+                        .collect(),
+                )
+            }
+            value => value,
+        };
+        LineSegment::deserialize(value).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for LineSegment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LineSegment::serialize(self, serializer)
+    }
+}
+
+/// Pairs a remote host with the `user.email` expected when pushing to it. Both
+/// fields accept `*`/`?` wildcards, e.g. `{ "host": "gitlab.corp.com", "email":
+/// "*@corp.com" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityRule {
+    pub host: String,
+    pub email: String,
+}
+
+/// A short name for the kubectl contexts matching `context` (`*`/`?` wildcards
+/// allowed), e.g. `{ "context": "arn:aws:eks:*:cluster/prod", "alias": "prod" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KubeAlias {
+    pub context: String,
+    pub alias: String,
+}
+
+/// The service the `pr` segment asks about the current branch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrProvider {
+    /// GitLab for a recognisable GitLab remote, GitHub otherwise.
+    #[default]
+    Auto,
+    /// GitHub pull requests, via `gh`.
+    GitHub,
+    /// GitLab merge requests, via `glab`.
+    GitLab,
+}
+
+/// The escape sequence used for terminal notifications. Terminals that don't
+/// understand it ignore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyStyle {
+    /// `OSC 9`, understood by iTerm2, Windows Terminal, WezTerm, kitty and
+    /// ConEmu.
+    Osc9,
+    /// `OSC 777`, understood by VTE-based terminals, foot and WezTerm.
+    Osc777,
+}
+
+/// What the git branch segment links to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchLink {
+    /// The branch's page (its file tree).
+    #[default]
+    Branch,
+    /// The diff between the remote's default branch and this one.
+    Compare,
+    /// Render the branch as plain text.
+    Off,
+}
+
+/// The web UIs we know the URL layout of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+}
+
+impl ForgeKind {
+    /// The name used for it in the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github",
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Bitbucket => "bitbucket",
+            ForgeKind::Gitea => "gitea",
+        }
+    }
+}
+
+impl std::str::FromStr for ForgeKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            ForgeKind::GitHub,
+            ForgeKind::GitLab,
+            ForgeKind::Bitbucket,
+            ForgeKind::Gitea,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == name)
+        .ok_or_else(|| format!("unknown forge `{}`", name))
+    }
+}
+
+/// Tells the git segments how to link into a self-hosted forge. Remotes whose
+/// host matches `host` (`*`/`?` wildcards allowed) either reuse a known layout
+/// via `kind`, or spell the URLs out with `{host}`, `{repo}`, `{branch}`,
+/// `{base}` (the default branch) and `{commit}` placeholders. An explicit
+/// template wins over the `kind` layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeTemplate {
+    pub host: String,
+    #[serde(default)]
+    pub kind: Option<ForgeKind>,
+    #[serde(default)]
+    pub branch_url: Option<String>,
+    #[serde(default)]
+    pub compare_url: Option<String>,
+    #[serde(default)]
+    pub commit_url: Option<String>,
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_hg_timeout() -> u64 {
+    500
+}
+
+/// Whole `prod`/`production` words at either end of the name. A plain
+/// `*prod*` would also catch `nonprod`, `preprod` and `non-production`.
+fn default_production() -> Vec<String> {
+    ["prod", "prod-*", "*-prod", "production", "production-*"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_battery_hide_above() -> u8 {
+    100
+}
+
+fn default_battery_warning() -> u8 {
+    20
+}
+
+fn default_battery_critical() -> u8 {
+    10
+}
+
+fn default_memory_above() -> u8 {
+    90
+}
+
+fn default_disk_below() -> u8 {
+    10
+}
+
+fn default_ticket_pattern() -> Regex {
+    Regex::new(r"([A-Z][A-Z0-9]+-\d+)").unwrap()
+}
+
+/// Ticket patterns are compiled as the config is read, so one that isn't a
+/// valid regex is rejected there rather than hiding the segment.
+mod ticket_pattern {
+    use regex::Regex;
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(pattern: &Regex, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(pattern.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+        Regex::new(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+fn default_pr_cache_ttl() -> u64 {
+    60
+}
+
+fn default_pr_refresh_debounce() -> u64 {
+    20
+}
+
+fn default_pr_skip_branches() -> Vec<String> {
+    ["develop", "main", "master", "HEAD"]
+        .map(String::from)
+        .to_vec()
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SeparatorStyle {
@@ -87,8 +515,25 @@ impl Default for Config {
                             resolve_symlinks: false,
                         },
                         LineSegment::Padding(2),
-                        LineSegment::Git,
-                        LineSegment::Pr { status: true },
+                        LineSegment::Git {
+                            submodules: false,
+                            worktrees: false,
+                            link: BranchLink::Branch,
+                            forges: vec![],
+                        },
+                        LineSegment::Pr {
+                            status: true,
+                            review: true,
+                            check_counts: false,
+                            ci: false,
+                            provider: PrProvider::Auto,
+                            api_url: None,
+                            cache_ttl: default_pr_cache_ttl(),
+                            refresh_debounce: default_pr_refresh_debounce(),
+                            skip_branches: default_pr_skip_branches(),
+                            notify: None,
+                            bell: false,
+                        },
                     ],
                     right: Some(vec![]),
                 },
@@ -116,6 +561,7 @@ impl Default for Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::glob_match;
 
     /// The binary writes `Config::default()` to disk with `to_string_pretty`
     /// and then reads it back with `from_reader`. This guards that round-trip:
@@ -136,4 +582,70 @@ mod tests {
             .expect("reparsed config should serialize to JSON");
         assert_eq!(json, reserialized);
     }
+
+    /// `git` was a bare string before it had options; existing configs using
+    /// that form must keep loading with every option at its default.
+    #[test]
+    fn bare_git_segment_still_parses() {
+        let bare: LineSegment = serde_json::from_str(r#""git""#).unwrap();
+        assert!(matches!(
+            bare,
+            LineSegment::Git {
+                submodules: false,
+                worktrees: false,
+                link: BranchLink::Branch,
+                ..
+            }
+        ));
+
+        let full: LineSegment =
+            serde_json::from_str(r#"{ "git": { "submodules": true } }"#).unwrap();
+        assert!(matches!(
+            full,
+            LineSegment::Git {
+                submodules: true,
+                worktrees: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn invalid_segments_keep_their_error_message() {
+        let err =
+            serde_json::from_str::<LineSegment>(r#"{ "cwd": { "max_length": 60 } }"#).unwrap_err();
+        assert!(err.to_string().contains("wanted_seg_num"), "{err}");
+    }
+
+    #[test]
+    fn default_production_patterns_skip_lookalikes() {
+        let production = default_production();
+        let is_production = |name| production.iter().any(|p| glob_match(p, name));
+        for name in ["prod", "prod-eu", "eks-prod", "production", "production-2"] {
+            assert!(is_production(name), "{name}");
+        }
+        for name in ["nonprod", "preprod", "non-production", "product", "staging"] {
+            assert!(!is_production(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn ticket_patterns_are_compiled_when_read() {
+        let ticket: LineSegment =
+            serde_json::from_str(r##"{ "git_ticket": { "pattern": "#(\\d+)", "url": "u" } }"##)
+                .unwrap();
+        let LineSegment::GitTicket { pattern, .. } = &ticket else {
+            panic!("not a git_ticket segment");
+        };
+        assert!(pattern.is_match("fix/#12"));
+        assert!(serde_json::to_string(&ticket)
+            .unwrap()
+            .contains(r##""#(\\d+)""##));
+
+        let err = serde_json::from_str::<LineSegment>(
+            r#"{ "git_ticket": { "pattern": "(unclosed", "url": "u" } }"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unclosed"), "{err}");
+    }
 }
@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod colors;
 pub mod config;
 pub mod modules;
@@ -8,5 +9,5 @@ pub mod themes;
 
 pub(crate) mod utils;
 
-pub use crate::powerline::{Powerline, Style};
+pub use crate::powerline::{Marker, Powerline, Style};
 pub use colors::Color;
@@ -1,16 +1,31 @@
 use crate::powerline::Powerline;
 
+mod aws;
+mod azure;
 mod cmd;
+mod container;
 mod cwd;
+mod disk;
+mod docker;
 mod exit_code;
+mod gcloud;
 mod git;
+mod hg;
 mod host;
+mod jj;
+mod jobs;
+mod kube;
+mod load;
+mod memory;
 mod pr;
 mod readonly;
+mod terraform;
 mod user;
 
+mod battery;
 mod cargo;
 mod cmd_duration;
+mod nix;
 mod nvm;
 mod python_env;
 mod sdkman_java;
@@ -18,23 +33,45 @@ mod shell_name;
 mod spacer;
 mod time;
 
+pub use aws::{Aws, AwsScheme};
+pub use azure::{Azure, AzureScheme};
+pub use battery::{Battery, BatteryScheme};
 pub use cargo::{Cargo, CargoScheme};
 pub use cmd::{Cmd, CmdScheme};
 pub use cmd_duration::{LastCmdDuration, LastCmdDurationScheme};
+pub use container::{Container, ContainerScheme};
 pub use cwd::{Cwd, CwdScheme};
+pub use disk::{Disk, DiskScheme};
+pub use docker::{Docker, DockerScheme};
 pub use exit_code::{ExitCode, ExitCodeScheme};
-pub use git::{Git, GitScheme};
+pub use gcloud::{Gcloud, GcloudScheme};
+pub use git::{
+    Git, GitCommit, GitCommitScheme, GitIdentity, GitIdentityScheme, GitScheme, GitTicket,
+    GitTicketScheme,
+};
+pub use hg::{refresh_hg, Hg, HgScheme};
 pub use host::{Host, HostScheme};
+pub use jj::{refresh_jj, Jj, JjScheme};
+pub use jobs::{Jobs, JobsScheme};
+pub use kube::{Kube, KubeScheme};
+pub use load::{Load, LoadScheme};
+pub use memory::{Memory, MemoryScheme};
+pub use nix::{Nix, NixScheme};
 pub use nvm::{Nvm, NvmScheme};
-pub use pr::{refresh_pr, Pr, PrScheme};
+pub use pr::{refresh_current_pr, refresh_pr, CachedPr, Pr, PrLookup, PrScheme};
 pub use python_env::{PythonEnv, PythonEnvScheme};
 pub use readonly::{ReadOnly, ReadOnlyScheme};
 pub use sdkman_java::{SdkmanJava, SdkmanScheme};
 pub use shell_name::{ShellName, ShellScheme};
 pub use spacer::{Spacer, SpacerScheme};
+pub use terraform::{Terraform, TerraformScheme};
 pub use time::{Time, TimeScheme};
 pub use user::{User, UserScheme};
 
 pub trait Module {
     fn append_segments(&mut self, powerline: &mut Powerline);
 }
+
+/// Looks up an environment variable. Modules that read their state from the
+/// environment take one as a parameter so tests can supply their own.
+type EnvGetter<'a> = dyn Fn(&str) -> Option<String> + 'a;
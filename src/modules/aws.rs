@@ -0,0 +1,255 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::ini_value;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the active AWS profile and region, and how long its credentials have
+/// left - from `aws-vault`'s environment, or the AWS CLI's SSO token cache.
+pub struct Aws<S> {
+    show_region: bool,
+    show_expiry: bool,
+    scheme: PhantomData<S>,
+}
+
+pub trait AwsScheme: DefaultColors {
+    fn aws_fg() -> Color {
+        Self::default_fg()
+    }
+    fn aws_bg() -> Color {
+        Self::default_bg()
+    }
+    fn aws_expired_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn aws_expired_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: AwsScheme> Aws<S> {
+    pub fn new(show_region: bool, show_expiry: bool) -> Aws<S> {
+        Aws {
+            show_region,
+            show_expiry,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const AWS_ICON: &str = "\u{e7ad}"; // nf-dev-aws
+const TIMER_ICON: &str = "\u{f017}"; // nf-fa-clock_o
+const EXPIRED_ICON: &str = "\u{f071}"; // nf-fa-warning
+
+impl<S: AwsScheme> Module for Aws<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(status) = read_status(&env, crate::platform::home_dir().as_deref()) else {
+            return;
+        };
+
+        let mut label = AWS_ICON.to_string();
+        if let Some(profile) = &status.profile {
+            label += &format!(" {}", profile);
+        }
+        if let Some(region) = status.region.filter(|_| self.show_region) {
+            label += &format!(" ({})", region);
+        }
+
+        let remaining = status
+            .expires_at
+            .filter(|_| self.show_expiry)
+            .map(|expires_at| (expires_at - Utc::now()).num_seconds());
+        let style = match remaining {
+            Some(secs) if secs <= 0 => {
+                label += &format!(" {}", EXPIRED_ICON);
+                Style::simple(S::aws_expired_fg(), S::aws_expired_bg())
+            }
+            Some(secs) => {
+                label += &format!(" {} {}", TIMER_ICON, format_remaining(secs));
+                Style::simple(S::aws_fg(), S::aws_bg())
+            }
+            None => Style::simple(S::aws_fg(), S::aws_bg()),
+        };
+        powerline.add_segment(label, style);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct AwsStatus {
+    profile: Option<String>,
+    region: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Works out the profile, region and credential expiry from the environment
+/// and the files under `home`. `None` unless a profile or region is selected
+/// through the environment.
+fn read_status(env: &EnvGetter, home: Option<&Path>) -> Option<AwsStatus> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    // aws-vault sets `AWS_VAULT` to the profile it's running under.
+    let profile = var("AWS_VAULT")
+        .or_else(|| var("AWS_PROFILE"))
+        .or_else(|| var("AWS_DEFAULT_PROFILE"));
+    let env_region = var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION"));
+    if profile.is_none() && env_region.is_none() {
+        return None;
+    }
+
+    let config_path = var("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(".aws").join("config")));
+    let config = config_path
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let section = match profile.as_deref().unwrap_or("default") {
+        "default" => "default".to_string(),
+        name => format!("profile {}", name),
+    };
+    let setting = |key: &str| ini_value(&config, &section, key);
+
+    let region = env_region.or_else(|| setting("region").map(ToOwned::to_owned));
+
+    // Credentials exported by aws-vault (or `aws configure export-credentials`)
+    // say when they expire; otherwise look for the profile's SSO login.
+    let expires_at = var("AWS_CREDENTIAL_EXPIRATION")
+        .or_else(|| var("AWS_SESSION_EXPIRATION"))
+        .and_then(|at| parse_timestamp(&at))
+        .or_else(|| {
+            let start_url = match setting("sso_session") {
+                Some(session) => ini_value(
+                    &config,
+                    &format!("sso-session {}", session),
+                    "sso_start_url",
+                ),
+                None => setting("sso_start_url"),
+            }?;
+            sso_expiry(&home?.join(".aws").join("sso").join("cache"), start_url)
+        });
+
+    Some(AwsStatus {
+        profile,
+        region,
+        expires_at,
+    })
+}
+
+/// The AWS CLI caches each SSO login's token in a JSON file named after a hash
+/// of the session. Rather than recompute the hash, pick the latest token for
+/// `start_url` out of the whole cache.
+fn sso_expiry(cache_dir: &Path, start_url: &str) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SsoToken {
+        start_url: Option<String>,
+        expires_at: Option<String>,
+    }
+
+    fs::read_dir(cache_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|json| serde_json::from_str::<SsoToken>(&json).ok())
+        .filter(|token| token.start_url.as_deref() == Some(start_url))
+        .filter_map(|token| parse_timestamp(&token.expires_at?))
+        .max()
+}
+
+/// RFC 3339, or the `2024-01-01T12:00:00UTC` form older CLI versions wrote.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|at| at.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%SUTC").map(|at| at.and_utc())
+        })
+        .ok()
+}
+
+/// `2d`, `3h05m`, `12m` or `<1m`.
+fn format_remaining(secs: i64) -> String {
+    match secs {
+        ..60 => "<1m".to_string(),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    fn scratch_home() -> TempDir {
+        let home = TempDir::new("aws");
+        fs::create_dir_all(home.join(".aws").join("sso").join("cache")).unwrap();
+        home
+    }
+
+    #[test]
+    fn reads_the_profile_region_and_sso_expiry() {
+        let home = scratch_home();
+        fs::write(
+            home.join(".aws").join("config"),
+            "[default]\nregion = us-east-1\n\
+             [profile deploy]\nsso_session = corp\nregion = eu-west-1\n\
+             [sso-session corp]\nsso_start_url = https://corp.awsapps.com/start\n",
+        )
+        .unwrap();
+        let cache = home.join(".aws").join("sso").join("cache");
+        fs::write(
+            cache.join("a.json"),
+            r#"{"startUrl": "https://corp.awsapps.com/start", "expiresAt": "2030-01-01T12:00:00Z"}"#,
+        )
+        .unwrap();
+        fs::write(
+            cache.join("b.json"),
+            r#"{"startUrl": "https://other.awsapps.com/start", "expiresAt": "2031-01-01T12:00:00Z"}"#,
+        )
+        .unwrap();
+        fs::write(
+            cache.join("botocore-client-id.json"),
+            r#"{"clientId": "x"}"#,
+        )
+        .unwrap();
+
+        let env = |key: &str| (key == "AWS_PROFILE").then(|| "deploy".to_string());
+        let status = read_status(&env, Some(&home)).unwrap();
+        assert_eq!(status.profile.as_deref(), Some("deploy"));
+        assert_eq!(status.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(status.expires_at, parse_timestamp("2030-01-01T12:00:00Z"));
+
+        // Nothing selected through the environment, nothing to show.
+        assert_eq!(read_status(&|_| None, Some(&home)), None);
+    }
+
+    #[test]
+    fn prefers_aws_vault_and_its_expiry() {
+        let env = |key: &str| match key {
+            "AWS_VAULT" => Some("admin".to_string()),
+            "AWS_PROFILE" => Some("ignored".to_string()),
+            "AWS_REGION" => Some("ap-south-1".to_string()),
+            "AWS_CREDENTIAL_EXPIRATION" => Some("2019-05-01T10:00:00UTC".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            read_status(&env, None),
+            Some(AwsStatus {
+                profile: Some("admin".to_string()),
+                region: Some("ap-south-1".to_string()),
+                expires_at: parse_timestamp("2019-05-01T10:00:00Z"),
+            })
+        );
+        assert_eq!(format_remaining(3 * 3600 + 5 * 60), "3h05m");
+        assert_eq!(format_remaining(30), "<1m");
+    }
+}
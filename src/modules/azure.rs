@@ -0,0 +1,136 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::glob_match;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the default Azure subscription and the account signed in to it, read
+/// from the CLI's `azureProfile.json`.
+pub struct Azure<S> {
+    show_account: bool,
+    /// Glob patterns for the subscriptions to show in the alert colours.
+    production: Vec<String>,
+    scheme: PhantomData<S>,
+}
+
+pub trait AzureScheme: DefaultColors {
+    fn azure_fg() -> Color {
+        Self::default_fg()
+    }
+    fn azure_bg() -> Color {
+        Self::default_bg()
+    }
+    fn azure_production_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn azure_production_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: AzureScheme> Azure<S> {
+    pub fn new(show_account: bool, production: Vec<String>) -> Azure<S> {
+        Azure {
+            show_account,
+            production,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const AZURE_ICON: &str = "\u{f0805}"; // nf-md-microsoft_azure
+
+impl<S: AzureScheme> Module for Azure<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(subscription) = config_dir(&env).and_then(|dir| default_subscription(&dir)) else {
+            return;
+        };
+
+        let mut label = format!("{} {}", AZURE_ICON, subscription.name);
+        if let Some(user) = subscription.user.filter(|_| self.show_account) {
+            label += &format!(" {}", user.name);
+        }
+
+        let production = self
+            .production
+            .iter()
+            .any(|pattern| glob_match(pattern, &subscription.name));
+        let style = if production {
+            Style::simple(S::azure_production_fg(), S::azure_production_bg())
+        } else {
+            Style::simple(S::azure_fg(), S::azure_bg())
+        };
+        powerline.add_segment(label, style);
+    }
+}
+
+/// `AZURE_CONFIG_DIR`, or `~/.azure`.
+fn config_dir(env: &EnvGetter) -> Option<PathBuf> {
+    match env("AZURE_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => crate::platform::home_dir().map(|home| home.join(".azure")),
+    }
+}
+
+#[derive(Deserialize)]
+struct AzureProfile {
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Subscription {
+    name: String,
+    #[serde(default)]
+    is_default: bool,
+    user: Option<SubscriptionUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscriptionUser {
+    name: String,
+}
+
+/// The subscription `az account set` last picked. The CLI writes the file with
+/// a byte order mark, which `serde_json` won't skip by itself.
+fn default_subscription(dir: &Path) -> Option<Subscription> {
+    let json = fs::read_to_string(dir.join("azureProfile.json")).ok()?;
+    let profile: AzureProfile = serde_json::from_str(json.trim_start_matches('\u{feff}')).ok()?;
+    profile
+        .subscriptions
+        .into_iter()
+        .find(|subscription| subscription.is_default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn finds_the_default_subscription() {
+        let dir = TempDir::new("azure");
+        fs::write(
+            dir.join("azureProfile.json"),
+            "\u{feff}{\"installationId\": \"x\", \"subscriptions\": [
+                {\"id\": \"1\", \"name\": \"Dev\", \"isDefault\": false, \"user\": {\"name\": \"me@corp.com\", \"type\": \"user\"}},
+                {\"id\": \"2\", \"name\": \"Production\", \"isDefault\": true, \"user\": {\"name\": \"me@corp.com\", \"type\": \"user\"}}
+            ]}",
+        )
+        .unwrap();
+
+        let subscription = default_subscription(&dir).unwrap();
+        assert_eq!(subscription.name, "Production");
+        assert_eq!(subscription.user.unwrap().name, "me@corp.com");
+        assert!(default_subscription(&dir.join("missing")).is_none());
+    }
+}
@@ -0,0 +1,276 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// Shows the battery's charge, read from Linux's sysfs, in the warning colours
+/// once it runs low. Hidden on machines without a battery.
+pub struct Battery<S> {
+    /// Where the power supplies are listed; `/sys/class/power_supply` except
+    /// in tests.
+    root: PathBuf,
+    /// Hide the segment above this charge, in percent.
+    hide_above: u8,
+    warning: u8,
+    critical: u8,
+    scheme: PhantomData<S>,
+}
+
+pub trait BatteryScheme: DefaultColors {
+    fn battery_fg() -> Color {
+        Self::default_fg()
+    }
+    fn battery_bg() -> Color {
+        Self::default_bg()
+    }
+    fn battery_low_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn battery_low_bg() -> Color {
+        Self::alert_bg()
+    }
+    fn battery_critical_fg() -> Color {
+        Self::battery_low_fg()
+    }
+    fn battery_critical_bg() -> Color {
+        Self::battery_low_bg()
+    }
+}
+
+impl<S: BatteryScheme> Battery<S> {
+    pub fn new(hide_above: u8, warning: u8, critical: u8) -> Battery<S> {
+        Battery {
+            root: PathBuf::from("/sys/class/power_supply"),
+            hide_above,
+            warning,
+            critical,
+            scheme: PhantomData,
+        }
+    }
+
+    /// Read the power supplies from `root` instead of sysfs.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+}
+
+const CHARGING_ICON: &str = "\u{f0e7}"; // nf-fa-bolt
+const LEVEL_ICONS: [&str; 5] = [
+    "\u{f244}", // nf-fa-battery_empty
+    "\u{f243}", // nf-fa-battery_quarter
+    "\u{f242}", // nf-fa-battery_half
+    "\u{f241}", // nf-fa-battery_three_quarters
+    "\u{f240}", // nf-fa-battery_full
+];
+
+impl<S: BatteryScheme> Module for Battery<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some(status) = read_status(&self.root) else {
+            return;
+        };
+        if status.percent > self.hide_above {
+            return;
+        }
+
+        let icon = if status.charging {
+            CHARGING_ICON
+        } else {
+            LEVEL_ICONS[(usize::from(status.percent) + 12) / 25]
+        };
+        // Plugged in, a low charge is nothing to worry about.
+        let style = match status.percent {
+            _ if status.charging => Style::simple(S::battery_fg(), S::battery_bg()),
+            percent if percent <= self.critical => {
+                Style::simple(S::battery_critical_fg(), S::battery_critical_bg())
+            }
+            percent if percent <= self.warning => {
+                Style::simple(S::battery_low_fg(), S::battery_low_bg())
+            }
+            _ => Style::simple(S::battery_fg(), S::battery_bg()),
+        };
+        powerline.add_segment(format!("{} {}%", icon, status.percent), style);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct BatteryStatus {
+    percent: u8,
+    charging: bool,
+}
+
+/// Combines every system battery under `root` (laptops with two count as one
+/// big one), skipping mains adapters and the batteries of wireless mice and
+/// the like. `None` without a battery.
+fn read_status(root: &Path) -> Option<BatteryStatus> {
+    let read = |dir: &Path, name: &str| {
+        fs::read_to_string(dir.join(name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let number = |dir: &Path, name: &str| read(dir, name)?.parse::<u64>().ok();
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    // Each battery's `(now, full)` along with the file names they came from,
+    // which tell the unit.
+    let mut levels = Vec::new();
+    let mut charging = false;
+    for dir in entries {
+        if read(&dir, "type").as_deref() != Some("Battery")
+            || read(&dir, "scope").as_deref() == Some("Device")
+        {
+            continue;
+        }
+        // The energy (µWh) or charge (µAh) figures weigh batteries of
+        // different sizes properly; `capacity` is the fallback.
+        let level = [("energy_now", "energy_full"), ("charge_now", "charge_full")]
+            .iter()
+            .find_map(|&(now, full)| Some((now, number(&dir, now)?, number(&dir, full)?)))
+            .filter(|&(_, _, full)| full > 0)
+            .or_else(|| Some(("capacity", number(&dir, "capacity")?, 100)));
+        let Some(level) = level else {
+            continue;
+        };
+        levels.push(level);
+        charging |= read(&dir, "status").as_deref() == Some("Charging");
+    }
+
+    (!levels.is_empty()).then(|| BatteryStatus {
+        percent: combined_percent(&levels).min(100) as u8,
+        charging,
+    })
+}
+
+/// Weighs the batteries by size when they all report in the same unit. Sums
+/// of µWh, µAh and percentages mean nothing together, so otherwise each
+/// battery's own percentage counts the same.
+fn combined_percent(levels: &[(&str, u64, u64)]) -> u64 {
+    let same_unit = levels.iter().all(|(unit, _, _)| *unit == levels[0].0);
+    if same_unit {
+        let now: u64 = levels.iter().map(|(_, now, _)| now).sum();
+        let full: u64 = levels.iter().map(|(_, _, full)| full).sum();
+        now * 100 / full.max(1)
+    } else {
+        let percents: u64 = levels.iter().map(|(_, now, full)| now * 100 / full).sum();
+        percents / levels.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    fn power_supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            fs::write(dir.join(file), format!("{}\n", contents)).unwrap();
+        }
+    }
+
+    #[test]
+    fn combines_the_system_batteries() {
+        let root = TempDir::new("battery");
+        assert_eq!(read_status(&root), None);
+
+        power_supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        power_supply(
+            &root,
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+        );
+        assert_eq!(read_status(&root), None);
+
+        power_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "90"),
+                ("energy_now", "45000000"),
+                ("energy_full", "50000000"),
+            ],
+        );
+        assert_eq!(
+            read_status(&root),
+            Some(BatteryStatus {
+                percent: 90,
+                charging: false,
+            })
+        );
+
+        // A small second battery that's nearly flat only drags the total down
+        // by its share of the energy.
+        power_supply(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("energy_now", "1000000"),
+                ("energy_full", "10000000"),
+            ],
+        );
+        assert_eq!(
+            read_status(&root),
+            Some(BatteryStatus {
+                percent: 76,
+                charging: true,
+            })
+        );
+    }
+
+    #[test]
+    fn averages_batteries_reporting_in_different_units() {
+        let root = TempDir::new("battery");
+        power_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("energy_now", "45000000"),
+                ("energy_full", "50000000"),
+            ],
+        );
+        power_supply(&root, "BAT1", &[("type", "Battery"), ("capacity", "30")]);
+        assert_eq!(
+            read_status(&root),
+            Some(BatteryStatus {
+                percent: 60,
+                charging: false,
+            })
+        );
+
+        // Energy and charge readings don't add up either.
+        power_supply(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("charge_now", "1000000"),
+                ("charge_full", "4000000"),
+            ],
+        );
+        fs::remove_file(root.join("BAT1").join("capacity")).unwrap();
+        assert_eq!(
+            read_status(&root),
+            Some(BatteryStatus {
+                percent: 57,
+                charging: false,
+            })
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the name of the container (or WSL distribution) the shell runs in, so
+/// a dev container's terminal can't be mistaken for the host's.
+pub struct Container<S> {
+    scheme: PhantomData<S>,
+}
+
+pub trait ContainerScheme: DefaultColors {
+    fn container_fg() -> Color {
+        Self::default_fg()
+    }
+    fn container_bg() -> Color {
+        Self::default_bg()
+    }
+
+    const CONTAINER_SYMBOL: &'static str = "\u{f4b7}"; // nf-oct-container
+    const WSL_SYMBOL: &'static str = "\u{f17a}"; // nf-fa-windows
+}
+
+impl<S: ContainerScheme> Default for Container<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: ContainerScheme> Container<S> {
+    pub fn new() -> Container<S> {
+        Container {
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<S: ContainerScheme> Module for Container<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(environment) = detect(&env, Path::new("/")) else {
+            return;
+        };
+
+        let symbol = match environment.kind {
+            Kind::Wsl => S::WSL_SYMBOL,
+            _ => S::CONTAINER_SYMBOL,
+        };
+        let name = environment
+            .name
+            .unwrap_or_else(|| environment.kind.name().to_string());
+        powerline.add_segment(
+            format!("{} {}", symbol, name),
+            Style::simple(S::container_fg(), S::container_bg()),
+        );
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Toolbox,
+    Distrobox,
+    Devcontainer,
+    Podman,
+    Docker,
+    Nspawn,
+    Wsl,
+    /// Another manager that announces itself through `/run/systemd/container`,
+    /// such as LXC.
+    Other(String),
+}
+
+impl Kind {
+    fn name(&self) -> &str {
+        match self {
+            Kind::Toolbox => "toolbox",
+            Kind::Distrobox => "distrobox",
+            Kind::Devcontainer => "devcontainer",
+            Kind::Podman => "podman",
+            Kind::Docker => "docker",
+            Kind::Nspawn => "systemd-nspawn",
+            Kind::Wsl => "wsl",
+            Kind::Other(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Environment {
+    kind: Kind,
+    name: Option<String>,
+}
+
+/// Works out what the shell runs in from the environment and the marker files
+/// under `root`. The more specific tools come first: toolbox, distrobox and
+/// devcontainers all run on top of Podman or Docker.
+fn detect(env: &EnvGetter, root: &Path) -> Option<Environment> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+    let read = |path: &str| {
+        fs::read_to_string(root.join(path))
+            .ok()
+            .map(|contents| contents.trim().to_string())
+            .filter(|contents| !contents.is_empty())
+    };
+    let hostname = || read("etc/hostname");
+
+    // Podman describes the container in `/run/.containerenv`; toolbox and
+    // distrobox containers are Podman (or Docker) ones too.
+    let containerenv = fs::read_to_string(root.join("run/.containerenv")).ok();
+    let podman_name = || {
+        containerenv.as_deref().and_then(|contents| {
+            contents.lines().find_map(|line| {
+                let value = line.strip_prefix("name=")?.trim_matches('"');
+                Some(value.to_string()).filter(|value| !value.is_empty())
+            })
+        })
+    };
+
+    let (kind, name) = if root.join("run/.toolboxenv").exists() {
+        (Kind::Toolbox, podman_name().or_else(hostname))
+    } else if var("DISTROBOX_ENTER_PATH").is_some() {
+        (Kind::Distrobox, var("CONTAINER_ID").or_else(hostname))
+    } else if var("REMOTE_CONTAINERS").is_some() || var("CODESPACES").is_some() {
+        // VS Code's dev containers and Codespaces, which build on them.
+        let workspace = var("LOCAL_WORKSPACE_FOLDER").and_then(|folder| {
+            let name = Path::new(&folder).file_name()?;
+            Some(name.to_string_lossy().into_owned())
+        });
+        let name = var("CODESPACE_NAME").or(workspace).or_else(hostname);
+        (Kind::Devcontainer, name)
+    } else if containerenv.is_some() {
+        (Kind::Podman, podman_name().or_else(hostname))
+    } else if root.join(".dockerenv").exists() {
+        (Kind::Docker, hostname())
+    } else if let Some(manager) = read("run/systemd/container") {
+        let kind = match manager.as_str() {
+            "systemd-nspawn" => Kind::Nspawn,
+            "docker" => Kind::Docker,
+            "podman" => Kind::Podman,
+            _ => Kind::Other(manager),
+        };
+        (kind, hostname())
+    } else if let Some(distro) = var("WSL_DISTRO_NAME") {
+        (Kind::Wsl, Some(distro))
+    } else {
+        return None;
+    };
+
+    Some(Environment { kind, name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    fn fake_root() -> TempDir {
+        let root = TempDir::new("container");
+        fs::create_dir_all(root.join("run").join("systemd")).unwrap();
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::write(root.join("etc").join("hostname"), "3f2a9c1b7d4e\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn detects_containers_from_marker_files() {
+        let root = fake_root();
+        assert_eq!(detect(&|_| None, &root), None);
+
+        fs::write(root.join(".dockerenv"), "").unwrap();
+        assert_eq!(
+            detect(&|_| None, &root),
+            Some(Environment {
+                kind: Kind::Docker,
+                name: Some("3f2a9c1b7d4e".to_string()),
+            })
+        );
+
+        // Podman's own description wins over Docker's marker.
+        fs::write(
+            root.join("run").join(".containerenv"),
+            "engine=\"podman-4.9.3\"\nname=\"fedora-dev\"\nid=\"abc\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect(&|_| None, &root),
+            Some(Environment {
+                kind: Kind::Podman,
+                name: Some("fedora-dev".to_string()),
+            })
+        );
+
+        fs::write(root.join("run").join(".toolboxenv"), "").unwrap();
+        assert_eq!(
+            detect(&|_| None, &root).map(|environment| environment.kind),
+            Some(Kind::Toolbox)
+        );
+    }
+
+    #[test]
+    fn detects_environments_from_variables() {
+        let root = fake_root();
+
+        let env = |key: &str| match key {
+            "REMOTE_CONTAINERS" => Some("true".to_string()),
+            "LOCAL_WORKSPACE_FOLDER" => Some("/home/me/src/shop".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            detect(&env, &root),
+            Some(Environment {
+                kind: Kind::Devcontainer,
+                name: Some("shop".to_string()),
+            })
+        );
+
+        let env = |key: &str| (key == "WSL_DISTRO_NAME").then(|| "Ubuntu-22.04".to_string());
+        assert_eq!(
+            detect(&env, &root),
+            Some(Environment {
+                kind: Kind::Wsl,
+                name: Some("Ubuntu-22.04".to_string()),
+            })
+        );
+
+        fs::write(
+            root.join("run").join("systemd").join("container"),
+            "systemd-nspawn\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect(&|_| None, &root).map(|environment| environment.kind),
+            Some(Kind::Nspawn)
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::marker::PhantomData;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{platform, Powerline, Style};
+
+use super::Module;
+
+/// Shows the space left on the current directory's filesystem once it drops
+/// to a threshold, before builds start failing for want of it.
+pub struct Disk<S> {
+    /// In percent of the filesystem's size.
+    below: u8,
+    scheme: PhantomData<S>,
+}
+
+pub trait DiskScheme: DefaultColors {
+    fn disk_fg() -> Color {
+        Self::default_fg()
+    }
+    fn disk_bg() -> Color {
+        Self::default_bg()
+    }
+}
+
+impl<S: DiskScheme> Disk<S> {
+    pub fn new(below: u8) -> Disk<S> {
+        Disk {
+            below,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const DISK_ICON: &str = "\u{f02ca}"; // nf-md-harddisk
+
+impl<S: DiskScheme> Module for Disk<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some((free, total)) = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| platform::disk_space(&cwd))
+        else {
+            return;
+        };
+        // Pseudo filesystems report no size at all.
+        if total == 0 || u128::from(free) * 100 > u128::from(total) * u128::from(self.below) {
+            return;
+        }
+        powerline.add_segment(
+            format!(
+                "{} {} ({}%)",
+                DISK_ICON,
+                format_bytes(free),
+                free * 100 / total
+            ),
+            Style::simple(S::disk_fg(), S::disk_bg()),
+        );
+    }
+}
+
+/// `512M`, `3.5G`, `120G`, `1.2T`: one decimal while it still says something.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 10.0 && unit > 0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_sizes() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(512 * 1024 * 1024), "512M");
+        assert_eq!(format_bytes(3584 * 1024 * 1024), "3.5G");
+        assert_eq!(format_bytes(120 * 1024 * 1024 * 1024), "120G");
+
+        #[cfg(unix)]
+        {
+            let (free, total) = platform::disk_space(&std::env::temp_dir()).unwrap();
+            assert!(total > 0 && free <= total);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the Docker context (or `DOCKER_HOST`) the CLI talks to, unless it's
+/// the local `default` one.
+pub struct Docker<S> {
+    scheme: PhantomData<S>,
+}
+
+pub trait DockerScheme: DefaultColors {
+    fn docker_fg() -> Color {
+        Self::default_fg()
+    }
+    fn docker_bg() -> Color {
+        Self::default_bg()
+    }
+    fn docker_remote_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn docker_remote_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: DockerScheme> Default for Docker<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: DockerScheme> Docker<S> {
+    pub fn new() -> Docker<S> {
+        Docker {
+            scheme: PhantomData,
+        }
+    }
+}
+
+const DOCKER_ICON: &str = "\u{f308}"; // nf-linux-docker
+
+impl<S: DockerScheme> Module for Docker<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(context) = current_context(&env, config_dir(&env).as_deref()) else {
+            return;
+        };
+
+        let style = if context.host.as_deref().is_some_and(is_remote) {
+            Style::simple(S::docker_remote_fg(), S::docker_remote_bg())
+        } else {
+            Style::simple(S::docker_fg(), S::docker_bg())
+        };
+        powerline.add_segment(format!("{} {}", DOCKER_ICON, context.name), style);
+    }
+}
+
+/// `DOCKER_CONFIG`, or `~/.docker`.
+fn config_dir(env: &EnvGetter) -> Option<PathBuf> {
+    match env("DOCKER_CONFIG").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => crate::platform::home_dir().map(|home| home.join(".docker")),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct DockerContext {
+    name: String,
+    /// The daemon's address, e.g. `ssh://builder` or `unix:///var/run/docker.sock`.
+    host: Option<String>,
+}
+
+/// Resolves the context the way the CLI does: `DOCKER_HOST` overrides any
+/// context, then `DOCKER_CONTEXT`, then `currentContext` in `config.json`.
+/// `None` for the `default` context.
+fn current_context(env: &EnvGetter, dir: Option<&Path>) -> Option<DockerContext> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    if let Some(host) = var("DOCKER_HOST") {
+        let name = host
+            .split_once("://")
+            .map_or(host.as_str(), |(_, rest)| rest);
+        return Some(DockerContext {
+            name: name.trim_end_matches('/').to_string(),
+            host: Some(host),
+        });
+    }
+
+    let name = var("DOCKER_CONTEXT").or_else(|| {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Config {
+            current_context: Option<String>,
+        }
+        let json = fs::read_to_string(dir?.join("config.json")).ok()?;
+        serde_json::from_str::<Config>(&json).ok()?.current_context
+    })?;
+    if name.is_empty() || name == "default" {
+        return None;
+    }
+
+    let host = dir.and_then(|dir| context_host(&dir.join("contexts").join("meta"), &name));
+    Some(DockerContext { name, host })
+}
+
+/// Each context's `meta.json` sits in a directory named after a hash of the
+/// context's name. Rather than recompute the hash, look through them all for
+/// the one with the right `Name`.
+fn context_host(meta_dir: &Path, name: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Meta {
+        name: String,
+        #[serde(default)]
+        endpoints: Endpoints,
+    }
+    #[derive(Default, Deserialize)]
+    struct Endpoints {
+        docker: Option<Endpoint>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Endpoint {
+        host: Option<String>,
+    }
+
+    fs::read_dir(meta_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join("meta.json")).ok())
+        .filter_map(|json| serde_json::from_str::<Meta>(&json).ok())
+        .find(|meta| meta.name == name)?
+        .endpoints
+        .docker?
+        .host
+}
+
+/// Anything but a local socket or the loopback address.
+fn is_remote(host: &str) -> bool {
+    let Some((scheme, rest)) = host.split_once("://") else {
+        return false;
+    };
+    if matches!(scheme, "unix" | "npipe" | "fd") {
+        return false;
+    }
+    let authority = rest.split('/').next().unwrap_or_default();
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let hostname = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    !matches!(hostname, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn reads_the_current_context() {
+        let dir = TempDir::new("docker");
+        let meta = dir.join("contexts").join("meta");
+        fs::create_dir_all(meta.join("4f2a")).unwrap();
+        fs::create_dir_all(meta.join("9c1b")).unwrap();
+        fs::write(
+            dir.join("config.json"),
+            r#"{"auths": {}, "currentContext": "builder"}"#,
+        )
+        .unwrap();
+        fs::write(
+            meta.join("4f2a").join("meta.json"),
+            r#"{"Name": "colima", "Endpoints": {"docker": {"Host": "unix:///home/me/.colima/docker.sock"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            meta.join("9c1b").join("meta.json"),
+            r#"{"Name": "builder", "Metadata": {}, "Endpoints": {"docker": {"Host": "ssh://ci@build1", "SkipTLSVerify": false}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            current_context(&|_| None, Some(&dir)),
+            Some(DockerContext {
+                name: "builder".to_string(),
+                host: Some("ssh://ci@build1".to_string()),
+            })
+        );
+
+        let env = |key: &str| (key == "DOCKER_CONTEXT").then(|| "default".to_string());
+        assert_eq!(current_context(&env, Some(&dir)), None);
+
+        let env = |key: &str| match key {
+            "DOCKER_CONTEXT" => Some("colima".to_string()),
+            "DOCKER_HOST" => Some("tcp://10.0.0.5:2376".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            current_context(&env, Some(&dir)),
+            Some(DockerContext {
+                name: "10.0.0.5:2376".to_string(),
+                host: Some("tcp://10.0.0.5:2376".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn tells_remote_hosts_from_local_ones() {
+        assert!(is_remote("ssh://ci@build1"));
+        assert!(is_remote("tcp://10.0.0.5:2376"));
+        assert!(!is_remote("unix:///var/run/docker.sock"));
+        assert!(!is_remote("npipe:////./pipe/docker_engine"));
+        assert!(!is_remote("tcp://localhost:2375"));
+        assert!(!is_remote("tcp://[::1]:2375"));
+    }
+}
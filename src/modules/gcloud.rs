@@ -0,0 +1,162 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::{glob_match, ini_value};
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the active gcloud project and account, read from the SDK's config
+/// files. The configuration's name is added when it isn't `default`.
+pub struct Gcloud<S> {
+    show_account: bool,
+    /// Glob patterns for the projects to show in the alert colours.
+    production: Vec<String>,
+    scheme: PhantomData<S>,
+}
+
+pub trait GcloudScheme: DefaultColors {
+    fn gcloud_fg() -> Color {
+        Self::default_fg()
+    }
+    fn gcloud_bg() -> Color {
+        Self::default_bg()
+    }
+    fn gcloud_production_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn gcloud_production_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: GcloudScheme> Gcloud<S> {
+    pub fn new(show_account: bool, production: Vec<String>) -> Gcloud<S> {
+        Gcloud {
+            show_account,
+            production,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const GCLOUD_ICON: &str = "\u{f11f6}"; // nf-md-google_cloud
+
+impl<S: GcloudScheme> Module for Gcloud<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(config) = read_config(&env, config_dir(&env).as_deref()) else {
+            return;
+        };
+
+        let mut label = format!("{} {}", GCLOUD_ICON, config.project);
+        if let Some(account) = config.account.filter(|_| self.show_account) {
+            label += &format!(" {}", account);
+        }
+        if config.name != "default" {
+            label += &format!(" [{}]", config.name);
+        }
+
+        let production = self
+            .production
+            .iter()
+            .any(|pattern| glob_match(pattern, &config.project));
+        let style = if production {
+            Style::simple(S::gcloud_production_fg(), S::gcloud_production_bg())
+        } else {
+            Style::simple(S::gcloud_fg(), S::gcloud_bg())
+        };
+        powerline.add_segment(label, style);
+    }
+}
+
+/// `CLOUDSDK_CONFIG`, or where the SDK keeps its config by default.
+fn config_dir(env: &EnvGetter) -> Option<PathBuf> {
+    if let Some(dir) = env("CLOUDSDK_CONFIG").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        env("APPDATA").map(|appdata| PathBuf::from(appdata).join("gcloud"))
+    } else {
+        crate::platform::home_dir().map(|home| home.join(".config").join("gcloud"))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct GcloudConfig {
+    name: String,
+    project: String,
+    account: Option<String>,
+}
+
+/// The active configuration - `CLOUDSDK_ACTIVE_CONFIG_NAME`, then the
+/// `active_config` file - and its `core` project and account, which the
+/// `CLOUDSDK_CORE_*` variables override. `None` without a project.
+fn read_config(env: &EnvGetter, dir: Option<&Path>) -> Option<GcloudConfig> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    let name = var("CLOUDSDK_ACTIVE_CONFIG_NAME")
+        .or_else(|| {
+            let active = fs::read_to_string(dir?.join("active_config")).ok()?;
+            Some(active.trim().to_string()).filter(|name| !name.is_empty())
+        })
+        .unwrap_or_else(|| "default".to_string());
+    let contents = dir
+        .and_then(|dir| {
+            fs::read_to_string(dir.join("configurations").join(format!("config_{}", name))).ok()
+        })
+        .unwrap_or_default();
+    let setting = |key: &str| ini_value(&contents, "core", key).map(ToOwned::to_owned);
+
+    Some(GcloudConfig {
+        project: var("CLOUDSDK_CORE_PROJECT").or_else(|| setting("project"))?,
+        account: var("CLOUDSDK_CORE_ACCOUNT").or_else(|| setting("account")),
+        name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn reads_the_active_configuration() {
+        let dir = TempDir::new("gcloud");
+        fs::create_dir_all(dir.join("configurations")).unwrap();
+        fs::write(dir.join("active_config"), "work\n").unwrap();
+        fs::write(
+            dir.join("configurations").join("config_work"),
+            "[core]\naccount = me@corp.com\nproject = billing-prod\n\n[compute]\nregion = europe-west1\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_config(&|_| None, Some(&dir)),
+            Some(GcloudConfig {
+                name: "work".to_string(),
+                project: "billing-prod".to_string(),
+                account: Some("me@corp.com".to_string()),
+            })
+        );
+
+        // The environment wins over the files.
+        let env = |key: &str| match key {
+            "CLOUDSDK_ACTIVE_CONFIG_NAME" => Some("personal".to_string()),
+            "CLOUDSDK_CORE_PROJECT" => Some("sandbox".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            read_config(&env, Some(&dir)),
+            Some(GcloudConfig {
+                name: "personal".to_string(),
+                project: "sandbox".to_string(),
+                account: None,
+            })
+        );
+        assert_eq!(read_config(&|_| None, None), None);
+    }
+}
@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use crate::colors::Color;
+use crate::config::ForgeTemplate;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::forge::ForgeRepo;
+use super::{find_git_dir, internal, Module};
+
+/// Shows the abbreviated `HEAD` commit hash, linked to the commit's page on the
+/// remote's web UI when the forge can be worked out.
+pub struct GitCommit<S> {
+    forges: Vec<ForgeTemplate>,
+    scheme: PhantomData<S>,
+}
+
+pub trait GitCommitScheme: DefaultColors {
+    fn git_commit_fg() -> Color {
+        Self::default_fg()
+    }
+    fn git_commit_bg() -> Color {
+        Self::default_bg()
+    }
+
+    const COMMIT_SYMBOL: &'static str = "\u{f417}";
+}
+
+impl<S: GitCommitScheme> GitCommit<S> {
+    pub fn new(forges: Vec<ForgeTemplate>) -> GitCommit<S> {
+        GitCommit {
+            forges,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const SHORT_HASH_LEN: usize = 7;
+
+impl<S: GitCommitScheme> Module for GitCommit<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some((git_dir, _)) = find_git_dir() else {
+            return;
+        };
+
+        let info = internal::read_forge_info(&git_dir);
+        let Some(head) = info.head else {
+            return;
+        };
+
+        let label = format!(
+            "{} {}",
+            S::COMMIT_SYMBOL,
+            &head[..SHORT_HASH_LEN.min(head.len())]
+        );
+        let style = Style::simple(S::git_commit_fg(), S::git_commit_bg());
+        let url = info
+            .remote_url
+            .as_deref()
+            .and_then(|url| ForgeRepo::new(url, &self.forges))
+            .and_then(|forge| forge.commit_url(&head));
+
+        match url {
+            Some(url) => powerline.add_hyperlink_segment(&label, &url, style, &[]),
+            None => powerline.add_segment(label, style),
+        }
+    }
+}
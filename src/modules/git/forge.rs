@@ -0,0 +1,205 @@
+use std::fmt::Write;
+
+use crate::config::{BranchLink, ForgeKind, ForgeTemplate};
+use crate::utils::glob_match;
+
+use super::parse_remote;
+
+/// A repository on a forge's web UI, together with the URL templates used to
+/// link into it. Built from a git remote URL; see [`ForgeTemplate`] for the
+/// placeholders.
+pub struct ForgeRepo<'a> {
+    host: &'a str,
+    repo: &'a str,
+    branch_url: Option<&'a str>,
+    compare_url: Option<&'a str>,
+    commit_url: Option<&'a str>,
+}
+
+/// Recognises the public forges (and the conventional `gitlab.` / `gitea.`
+/// host names of self-hosted instances) from a remote's host.
+pub(super) fn detect(host: &str) -> Option<ForgeKind> {
+    match host {
+        "github.com" => Some(ForgeKind::GitHub),
+        "bitbucket.org" => Some(ForgeKind::Bitbucket),
+        // Codeberg runs Forgejo, which keeps Gitea's URL layout.
+        "gitea.com" | "codeberg.org" => Some(ForgeKind::Gitea),
+        _ if host == "gitlab.com" || host.starts_with("gitlab.") => Some(ForgeKind::GitLab),
+        _ if host.starts_with("gitea.") => Some(ForgeKind::Gitea),
+        _ => None,
+    }
+}
+
+/// The branch, compare and commit URL templates of a known forge.
+fn layout(kind: ForgeKind) -> [&'static str; 3] {
+    match kind {
+        ForgeKind::GitHub => [
+            "https://{host}/{repo}/tree/{branch}",
+            "https://{host}/{repo}/compare/{base}...{branch}",
+            "https://{host}/{repo}/commit/{commit}",
+        ],
+        ForgeKind::GitLab => [
+            "https://{host}/{repo}/-/tree/{branch}",
+            "https://{host}/{repo}/-/compare/{base}...{branch}",
+            "https://{host}/{repo}/-/commit/{commit}",
+        ],
+        ForgeKind::Bitbucket => [
+            "https://{host}/{repo}/src/{branch}",
+            "https://{host}/{repo}/branches/compare/{branch}%0D{base}",
+            "https://{host}/{repo}/commits/{commit}",
+        ],
+        ForgeKind::Gitea => [
+            "https://{host}/{repo}/src/branch/{branch}",
+            "https://{host}/{repo}/compare/{base}...{branch}",
+            "https://{host}/{repo}/commit/{commit}",
+        ],
+    }
+}
+
+impl<'a> ForgeRepo<'a> {
+    /// Resolves the forge behind `remote_url`: the first template whose host
+    /// pattern matches, then the built-in layouts. `None` for local remotes
+    /// and hosts we know nothing about.
+    pub fn new(remote_url: &'a str, templates: &'a [ForgeTemplate]) -> Option<Self> {
+        let (host, repo) = parse_remote(remote_url)?;
+        let template = templates.iter().find(|t| glob_match(&t.host, host));
+        let [branch, compare, commit] = match template.and_then(|t| t.kind).or_else(|| detect(host))
+        {
+            Some(kind) => layout(kind).map(Some),
+            None => [None; 3],
+        };
+        let custom = |pick: fn(&'a ForgeTemplate) -> &'a Option<String>| {
+            template.and_then(|t| pick(t).as_deref())
+        };
+
+        let forge = ForgeRepo {
+            host,
+            repo,
+            branch_url: custom(|t| &t.branch_url).or(branch),
+            compare_url: custom(|t| &t.compare_url).or(compare),
+            commit_url: custom(|t| &t.commit_url).or(commit),
+        };
+        (forge.branch_url.is_some() || forge.compare_url.is_some() || forge.commit_url.is_some())
+            .then_some(forge)
+    }
+
+    /// Links to `branch`, or to its diff against `base` for
+    /// [`BranchLink::Compare`]. Falls back to the branch page when there's no
+    /// base to compare against.
+    pub fn branch_url(&self, branch: &str, base: Option<&str>, link: BranchLink) -> Option<String> {
+        let compare = match (link, base) {
+            (BranchLink::Compare, Some(base)) if base != branch => self
+                .compare_url
+                .map(|template| self.expand(template, branch, base, "")),
+            _ => None,
+        };
+        compare.or_else(|| {
+            self.branch_url
+                .map(|template| self.expand(template, branch, base.unwrap_or_default(), ""))
+        })
+    }
+
+    pub fn commit_url(&self, commit: &str) -> Option<String> {
+        self.commit_url
+            .map(|template| self.expand(template, "", "", commit))
+    }
+
+    fn expand(&self, template: &str, branch: &str, base: &str, commit: &str) -> String {
+        template
+            .replace("{host}", self.host)
+            .replace("{repo}", self.repo)
+            .replace("{branch}", &encode_ref(branch))
+            .replace("{base}", &encode_ref(base))
+            .replace("{commit}", commit)
+    }
+}
+
+/// Percent-encodes a ref name for use in a URL path. `/` is kept as is, since
+/// forges expect `feature/x` to appear literally in branch URLs.
+pub(super) fn encode_ref(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(host: &str) -> ForgeTemplate {
+        ForgeTemplate {
+            host: host.into(),
+            kind: None,
+            branch_url: None,
+            compare_url: None,
+            commit_url: None,
+        }
+    }
+
+    #[test]
+    fn links_into_known_forges() {
+        let github = ForgeRepo::new("git@github.com:a/b.git", &[]).unwrap();
+        assert_eq!(
+            github.branch_url("feature/x", Some("main"), BranchLink::Branch),
+            Some("https://github.com/a/b/tree/feature/x".into())
+        );
+        assert_eq!(
+            github.commit_url("abc123"),
+            Some("https://github.com/a/b/commit/abc123".into())
+        );
+
+        let gitlab = ForgeRepo::new("https://gitlab.corp.com/g/sub/p.git", &[]).unwrap();
+        assert_eq!(
+            gitlab.branch_url("fix", Some("main"), BranchLink::Compare),
+            Some("https://gitlab.corp.com/g/sub/p/-/compare/main...fix".into())
+        );
+    }
+
+    #[test]
+    fn compare_falls_back_to_the_branch_page() {
+        let gitea = ForgeRepo::new("https://codeberg.org/a/b", &[]).unwrap();
+        assert_eq!(
+            gitea.branch_url("main", Some("main"), BranchLink::Compare),
+            Some("https://codeberg.org/a/b/src/branch/main".into())
+        );
+        assert_eq!(
+            gitea.branch_url("fix", None, BranchLink::Compare),
+            Some("https://codeberg.org/a/b/src/branch/fix".into())
+        );
+    }
+
+    #[test]
+    fn templates_cover_self_hosted_forges() {
+        assert!(ForgeRepo::new("git@git.corp.com:a/b.git", &[]).is_none());
+
+        let templates = [ForgeTemplate {
+            kind: Some(ForgeKind::Gitea),
+            commit_url: Some("https://code.corp.com/{repo}/c/{commit}".into()),
+            ..template("git.corp.*")
+        }];
+        let forge = ForgeRepo::new("git@git.corp.com:a/b.git", &templates).unwrap();
+        assert_eq!(
+            forge.branch_url("fix", None, BranchLink::Branch),
+            Some("https://git.corp.com/a/b/src/branch/fix".into())
+        );
+        assert_eq!(
+            forge.commit_url("abc123"),
+            Some("https://code.corp.com/a/b/c/abc123".into())
+        );
+    }
+
+    #[test]
+    fn branch_names_are_percent_encoded() {
+        assert_eq!(encode_ref("feature/x-1.2"), "feature/x-1.2");
+        assert_eq!(encode_ref("fix#12 wip"), "fix%2312%20wip");
+    }
+}
@@ -4,12 +4,12 @@ use gix::status::index_worktree::Item as IndexWorktreeItem;
 use gix::status::plumbing::index_as_worktree::EntryStatus;
 use gix::status::Item;
 
-use super::GitStats;
+use super::{ForgeInfo, GitIdentityInfo, GitStats, StatsOptions, SubmoduleStats};
 
 /// gitoxide (pure-Rust) git backend. Produces the same [`GitStats`] the libgit
 /// and CLI backends do: a count of staged / non-staged / untracked / conflicted
 /// paths plus the ahead/behind distance from the upstream tracking branch.
-pub fn run_git(path: &Path) -> GitStats {
+pub fn run_git(path: &Path, options: StatsOptions) -> GitStats {
     let repo = gix::discover(path).unwrap();
 
     let (mut untracked, mut staged, mut non_staged, mut conflicted) = (0u32, 0, 0, 0);
@@ -18,10 +18,12 @@ pub fn run_git(path: &Path) -> GitStats {
     // index-vs-worktree entries are either modifications, conflicts, renames,
     // or untracked directory contents. Untracked listing follows the repo's
     // `status.showUntrackedFiles` config (collapsed directories by default),
-    // matching the other backends.
+    // matching the other backends. Submodule working trees are skipped here and
+    // summarised separately in `SubmoduleStats`.
     let status = repo
         .status(gix::progress::Discard)
         .unwrap()
+        .index_worktree_submodules(None)
         .into_iter(None)
         .unwrap();
 
@@ -77,6 +79,15 @@ pub fn run_git(path: &Path) -> GitStats {
         }
     }
 
+    let config = repo.config_snapshot();
+    let sparse_checkout = config.boolean("core.sparseCheckout").unwrap_or(false);
+    let partial_clone = config.string("extensions.partialClone").is_some()
+        || repo.remote_names().iter().any(|name| {
+            config
+                .boolean(format!("remote.{}.promisor", name).as_str())
+                .unwrap_or(false)
+        });
+
     GitStats {
         untracked,
         staged,
@@ -86,6 +97,158 @@ pub fn run_git(path: &Path) -> GitStats {
         conflicted,
         remote,
         branch_name,
+        submodules: if options.submodules {
+            submodule_stats(&repo)
+        } else {
+            SubmoduleStats::default()
+        },
+        sparse_checkout,
+        partial_clone,
+        dirty_worktrees: if options.worktrees {
+            dirty_worktrees(&repo)
+        } else {
+            0
+        },
+    }
+}
+
+/// Counts the repository's other worktrees (the main one included, when we're
+/// in a linked worktree) whose index or tracked files differ from `HEAD`.
+fn dirty_worktrees(repo: &gix::Repository) -> u32 {
+    let current = repo.workdir().and_then(|dir| dir.canonicalize().ok());
+    let Ok(main) = repo.main_repo() else {
+        return 0;
+    };
+
+    let linked: Vec<gix::Repository> = main
+        .worktrees()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|proxy| proxy.into_repo().ok())
+        .collect();
+
+    let mut dirty = 0;
+    for other in linked.iter().chain(Some(&main)) {
+        let Some(workdir) = other.workdir().and_then(|dir| dir.canonicalize().ok()) else {
+            continue;
+        };
+        if Some(workdir) != current && other.is_dirty().unwrap_or(false) {
+            dirty += 1;
+        }
+    }
+    dirty
+}
+
+/// Runs a full status inside each submodule to sort it into the same buckets
+/// `git status` reports: new commits, modified content, untracked content, or
+/// not initialized at all.
+fn submodule_stats(repo: &gix::Repository) -> SubmoduleStats {
+    let mut stats = SubmoduleStats::default();
+    let Ok(Some(submodules)) = repo.submodules() else {
+        return stats;
+    };
+
+    for submodule in submodules {
+        let Ok(status) = submodule.status(gix::submodule::config::Ignore::None, false) else {
+            continue;
+        };
+
+        let uninitialized = !status.state.repository_exists || !status.state.worktree_checkout;
+        let new_commits =
+            status.checked_out_head_id.is_some() && status.checked_out_head_id != status.index_id;
+        let changes = status.changes.as_deref().unwrap_or_default();
+        let untracked = changes.iter().any(|change| {
+            matches!(
+                change,
+                Item::IndexWorktree(IndexWorktreeItem::DirectoryContents { .. })
+            )
+        });
+        let modified = changes.iter().any(|change| {
+            !matches!(
+                change,
+                Item::IndexWorktree(IndexWorktreeItem::DirectoryContents { .. })
+            )
+        });
+
+        stats.count(new_commits, modified, untracked, uninitialized);
+    }
+
+    stats
+}
+
+/// Reads the committer identity from the resolved config snapshot. The remote
+/// follows `git fetch`'s own defaulting (branch upstream, then `origin`, then
+/// the only remote) via [`gix::Repository::find_fetch_remote`].
+pub fn read_identity(path: &Path) -> GitIdentityInfo {
+    let Ok(repo) = gix::discover(path) else {
+        return GitIdentityInfo {
+            email: None,
+            signing_key: None,
+            remote_url: None,
+        };
+    };
+
+    let config = repo.config_snapshot();
+    let get = |key: &str| config.string(key).map(|value| value.to_string());
+
+    GitIdentityInfo {
+        email: get("user.email"),
+        signing_key: get("user.signingkey"),
+        remote_url: repo.find_fetch_remote(None).ok().and_then(|remote| {
+            remote
+                .url(gix::remote::Direction::Fetch)
+                .map(|url| url.to_bstring().to_string())
+        }),
+    }
+}
+
+/// Reads what the forge links need. The remote is resolved like `git fetch`
+/// would, and the default branch comes from the `refs/remotes/<remote>/HEAD`
+/// symref that `git clone` (or `git remote set-head`) leaves behind.
+pub fn read_forge_info(path: &Path) -> ForgeInfo {
+    let Ok(repo) = gix::discover(path) else {
+        return ForgeInfo::default();
+    };
+
+    let direction = gix::remote::Direction::Fetch;
+    let head_name = repo.head_name().ok().flatten();
+    // `branch.<name>.merge` is only meaningful alongside `branch.<name>.remote`.
+    let remote_branch = head_name.as_ref().and_then(|name| {
+        repo.branch_remote_name(name.shorten(), direction)?;
+        let merge = repo
+            .branch_remote_ref_name(name.as_ref(), direction)?
+            .ok()?;
+        Some(merge.shorten().to_string())
+    });
+
+    let remote = repo.find_fetch_remote(None).ok();
+    let default_branch = remote
+        .as_ref()
+        .and_then(|remote| remote.name())
+        .and_then(|name| {
+            let prefix = format!("refs/remotes/{}/", name.as_bstr());
+            let reference = repo
+                .find_reference(format!("{}HEAD", prefix).as_str())
+                .ok()?;
+            match reference.target() {
+                gix::refs::TargetRef::Symbolic(target) => target
+                    .as_bstr()
+                    .to_string()
+                    .strip_prefix(&prefix)
+                    .map(ToOwned::to_owned),
+                gix::refs::TargetRef::Object(_) => None,
+            }
+        });
+
+    ForgeInfo {
+        head: repo.head_id().ok().map(|id| id.to_string()),
+        branch: head_name.as_ref().map(|name| name.shorten().to_string()),
+        remote_url: remote
+            .as_ref()
+            .and_then(|remote| remote.url(direction))
+            .map(|url| url.to_bstring().to_string()),
+        remote_branch,
+        default_branch,
     }
 }
 
@@ -150,49 +313,26 @@ fn count_commits(repo: &gix::Repository, tip: gix::ObjectId, excluded: gix::Obje
 
 #[cfg(test)]
 mod tests {
-    use std::path::{Path, PathBuf};
-    use std::process::Command;
-    use std::sync::atomic::{AtomicU32, Ordering};
-
-    use super::{dir_contains_file, run_git};
-
-    fn unique_temp_dir() -> PathBuf {
-        static COUNTER: AtomicU32 = AtomicU32::new(0);
-        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
-        let dir = std::env::temp_dir().join(format!("superline-gix-{}-{n}", std::process::id()));
-        std::fs::create_dir_all(&dir).unwrap();
-        dir
-    }
-
-    fn git(repo: &Path, args: &[&str]) {
-        let status = Command::new("git")
-            .current_dir(repo)
-            .args(args)
-            .status()
-            .unwrap();
-        assert!(status.success(), "`git {}` failed", args.join(" "));
-    }
+    use super::super::fixtures::init_repo;
+    use super::{dir_contains_file, run_git, StatsOptions};
+    use crate::utils::TempDir;
 
-    fn init_repo() -> PathBuf {
-        let dir = unique_temp_dir();
-        git(&dir, &["init", "-q", "-b", "main"]);
-        git(&dir, &["config", "user.email", "test@example.com"]);
-        git(&dir, &["config", "user.name", "test"]);
-        git(&dir, &["commit", "-q", "--allow-empty", "-m", "init"]);
-        dir
-    }
+    const OPTIONS: StatsOptions = StatsOptions {
+        submodules: true,
+        worktrees: true,
+    };
 
     /// Regression test: gitoxide's collapsing walk emits an untracked directory
     /// even when it contains nothing but empty subdirectories, where `git
     /// status` reports a clean tree. Such a directory must not be counted.
     #[test]
     fn untracked_dir_of_only_empty_subdirs_is_not_counted() {
-        let repo = init_repo();
+        let repo = init_repo("gix");
         std::fs::create_dir_all(repo.join("screenshots/new")).unwrap();
         std::fs::create_dir_all(repo.join("screenshots/reference")).unwrap();
 
         assert_eq!(
-            run_git(&repo).untracked,
+            run_git(&repo, OPTIONS).untracked,
             0,
             "a directory holding only empty subdirectories must not count as untracked"
         );
@@ -201,17 +341,15 @@ mod tests {
         // directory, which git collapses into a single entry.
         std::fs::write(repo.join("screenshots/new/shot.png"), b"x").unwrap();
         assert_eq!(
-            run_git(&repo).untracked,
+            run_git(&repo, OPTIONS).untracked,
             1,
             "an untracked directory containing a file counts once"
         );
-
-        std::fs::remove_dir_all(&repo).ok();
     }
 
     #[test]
     fn dir_contains_file_finds_nested_files_only() {
-        let dir = unique_temp_dir();
+        let dir = TempDir::new("gix");
         std::fs::create_dir_all(dir.join("a/b/c")).unwrap();
         assert!(
             !dir_contains_file(&dir),
@@ -220,7 +358,5 @@ mod tests {
 
         std::fs::write(dir.join("a/b/c/leaf"), b"x").unwrap();
         assert!(dir_contains_file(&dir), "a nested file must be found");
-
-        std::fs::remove_dir_all(&dir).ok();
     }
 }
@@ -0,0 +1,191 @@
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::colors::Color;
+use crate::config::IdentityRule;
+use crate::themes::DefaultColors;
+use crate::utils::glob_match;
+use crate::{Powerline, Style};
+
+use super::{find_git_dir, internal, remote_host, Module};
+
+/// Shows the `user.email` git will commit with, checked against the email
+/// pattern configured for the repository's remote host.
+pub struct GitIdentity<S> {
+    rules: Vec<IdentityRule>,
+    only_on_mismatch: bool,
+    show_signing_key: bool,
+    scheme: PhantomData<S>,
+}
+
+pub trait GitIdentityScheme: DefaultColors {
+    fn git_identity_fg() -> Color {
+        Self::default_fg()
+    }
+    fn git_identity_bg() -> Color {
+        Self::default_bg()
+    }
+    fn git_identity_mismatch_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn git_identity_mismatch_bg() -> Color {
+        Self::alert_bg()
+    }
+
+    const IDENTITY_SYMBOL: &'static str = "\u{f007}";
+    const SIGNING_KEY_SYMBOL: &'static str = "\u{f084}";
+}
+
+impl<S: GitIdentityScheme> GitIdentity<S> {
+    pub fn new(
+        rules: Vec<IdentityRule>,
+        only_on_mismatch: bool,
+        show_signing_key: bool,
+    ) -> GitIdentity<S> {
+        GitIdentity {
+            rules,
+            only_on_mismatch,
+            show_signing_key,
+            scheme: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum IdentityCheck {
+    /// No rule covers this remote host, so there's nothing to compare against.
+    Unchecked,
+    Matches,
+    Mismatch,
+}
+
+/// Compares `email` against the first rule whose host pattern matches `host`.
+/// An unset email never satisfies a rule.
+fn check_identity(
+    rules: &[IdentityRule],
+    host: Option<&str>,
+    email: Option<&str>,
+) -> IdentityCheck {
+    let Some(rule) = host.and_then(|host| rules.iter().find(|r| glob_match(&r.host, host))) else {
+        return IdentityCheck::Unchecked;
+    };
+
+    match email {
+        Some(email) if glob_match(&rule.email, email) => IdentityCheck::Matches,
+        _ => IdentityCheck::Mismatch,
+    }
+}
+
+/// A short, recognisable form of `user.signingkey`: the file name for SSH key
+/// paths, otherwise the last 8 characters of a GPG key id or fingerprint.
+fn short_signing_key(key: &str) -> &str {
+    if key.contains(['/', '\\']) {
+        return Path::new(key)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(key);
+    }
+
+    match key.char_indices().rev().nth(7) {
+        Some((idx, _)) => &key[idx..],
+        None => key,
+    }
+}
+
+impl<S: GitIdentityScheme> Module for GitIdentity<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some((git_dir, _)) = find_git_dir() else {
+            return;
+        };
+
+        let identity = internal::read_identity(&git_dir);
+        let host = identity.remote_url.as_deref().and_then(remote_host);
+        let check = check_identity(&self.rules, host, identity.email.as_deref());
+
+        if self.only_on_mismatch && check != IdentityCheck::Mismatch {
+            return;
+        }
+
+        let (fg, bg) = match check {
+            IdentityCheck::Mismatch => {
+                (S::git_identity_mismatch_fg(), S::git_identity_mismatch_bg())
+            }
+            _ => (S::git_identity_fg(), S::git_identity_bg()),
+        };
+
+        let mut label = format!(
+            "{} {}",
+            S::IDENTITY_SYMBOL,
+            identity.email.as_deref().unwrap_or("no email")
+        );
+        if self.show_signing_key {
+            if let Some(key) = identity.signing_key.as_deref() {
+                label = format!(
+                    "{} {} {}",
+                    label,
+                    S::SIGNING_KEY_SYMBOL,
+                    short_signing_key(key)
+                );
+            }
+        }
+
+        powerline.add_segment(label, Style::simple(fg, bg));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<IdentityRule> {
+        vec![
+            IdentityRule {
+                host: "gitlab.corp.com".into(),
+                email: "*@corp.com".into(),
+            },
+            IdentityRule {
+                host: "github.com".into(),
+                email: "me@personal.dev".into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn matching_email_passes() {
+        assert_eq!(
+            check_identity(&rules(), Some("gitlab.corp.com"), Some("alex@corp.com")),
+            IdentityCheck::Matches
+        );
+    }
+
+    #[test]
+    fn personal_email_on_work_remote_is_a_mismatch() {
+        assert_eq!(
+            check_identity(&rules(), Some("gitlab.corp.com"), Some("me@personal.dev")),
+            IdentityCheck::Mismatch
+        );
+        assert_eq!(
+            check_identity(&rules(), Some("gitlab.corp.com"), None),
+            IdentityCheck::Mismatch
+        );
+    }
+
+    #[test]
+    fn hosts_without_a_rule_are_unchecked() {
+        assert_eq!(
+            check_identity(&rules(), Some("bitbucket.org"), Some("me@personal.dev")),
+            IdentityCheck::Unchecked
+        );
+        assert_eq!(
+            check_identity(&rules(), None, Some("me@personal.dev")),
+            IdentityCheck::Unchecked
+        );
+    }
+
+    #[test]
+    fn signing_keys_are_shortened() {
+        assert_eq!(short_signing_key("~/.ssh/id_ed25519.pub"), "id_ed25519.pub");
+        assert_eq!(short_signing_key("3AA5C34371567BD2"), "71567BD2");
+        assert_eq!(short_signing_key("ABCD"), "ABCD");
+    }
+}
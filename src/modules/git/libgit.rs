@@ -1,11 +1,16 @@
+use std::fs;
 use std::path::Path;
 
-use git2::{Branch, BranchType, ObjectType, Repository, Status, StatusOptions, StatusShow};
+use git2::{
+    Branch, BranchType, ObjectType, Repository, Status, StatusOptions, StatusShow, SubmoduleIgnore,
+    SubmoduleStatus,
+};
 
-use super::GitStats;
+use super::{ForgeInfo, GitIdentityInfo, GitStats, StatsOptions, SubmoduleStats};
 
-pub fn run_git(path: &Path) -> GitStats {
+pub fn run_git(path: &Path, options: StatsOptions) -> GitStats {
     let repository = Repository::open(path).unwrap();
+    let submodules = repository.submodules().unwrap_or_default();
 
     let mut status_options = StatusOptions::new();
     status_options
@@ -18,12 +23,25 @@ pub fn run_git(path: &Path) -> GitStats {
     let (mut untracked, mut non_staged, mut conflicted, mut staged, mut ahead, mut behind) =
         (0, 0, 0, 0, 0, 0);
 
-    for status in repository
+    for entry in repository
         .statuses(Some(&mut status_options))
         .unwrap()
         .iter()
-        .map(|ref x| x.status())
     {
+        let mut status = entry.status();
+        // A submodule's working tree state is tracked separately in
+        // `SubmoduleStats`; only a staged gitlink change counts here.
+        if submodules
+            .iter()
+            .any(|sm| Some(sm.path()) == entry.path().map(Path::new))
+        {
+            status &= Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_TYPECHANGE
+                | Status::INDEX_RENAMED
+                | Status::INDEX_DELETED;
+        }
+
         if status.intersects(
             Status::INDEX_NEW
                 | Status::INDEX_MODIFIED
@@ -87,6 +105,40 @@ pub fn run_git(path: &Path) -> GitStats {
             }
         });
 
+    let mut submodule_stats = SubmoduleStats::default();
+    if options.submodules {
+        for submodule in submodules.iter().filter_map(|sm| sm.name()) {
+            let Ok(status) = repository.submodule_status(submodule, SubmoduleIgnore::None) else {
+                continue;
+            };
+            submodule_stats.count(
+                status.is_wd_modified(),
+                status.intersects(
+                    SubmoduleStatus::WD_WD_MODIFIED | SubmoduleStatus::WD_INDEX_MODIFIED,
+                ),
+                status.is_wd_untracked(),
+                status.is_wd_uninitialized(),
+            );
+        }
+    }
+
+    let config = repository.config().ok();
+    let is_set = |key: &str| {
+        config
+            .as_ref()
+            .is_some_and(|c| c.get_bool(key).unwrap_or(false))
+    };
+    let sparse_checkout = is_set("core.sparseCheckout");
+    let partial_clone = config
+        .as_ref()
+        .is_some_and(|c| c.get_string("extensions.partialClone").is_ok())
+        || repository.remotes().is_ok_and(|remotes| {
+            remotes
+                .iter()
+                .flatten()
+                .any(|name| is_set(&format!("remote.{}.promisor", name)))
+        });
+
     GitStats {
         untracked,
         staged,
@@ -96,5 +148,153 @@ pub fn run_git(path: &Path) -> GitStats {
         conflicted,
         remote,
         branch_name,
+        submodules: submodule_stats,
+        sparse_checkout,
+        partial_clone,
+        dirty_worktrees: if options.worktrees {
+            dirty_worktrees(&repository)
+        } else {
+            0
+        },
+    }
+}
+
+/// Counts the repository's other worktrees (the main one included, when we're
+/// in a linked worktree) that have changes to tracked files.
+fn dirty_worktrees(repository: &Repository) -> u32 {
+    let current = repository.workdir().and_then(|dir| dir.canonicalize().ok());
+    // git2 doesn't expose `$GIT_COMMON_DIR`, but a linked worktree's private
+    // git dir records it in its `commondir` file.
+    let common_dir = if repository.is_worktree() {
+        let Ok(common_dir) = fs::read_to_string(repository.path().join("commondir")) else {
+            return 0;
+        };
+        repository.path().join(common_dir.trim())
+    } else {
+        repository.path().to_path_buf()
+    };
+    let Ok(main) = Repository::open(common_dir) else {
+        return 0;
+    };
+
+    let linked = main.worktrees().ok();
+    let linked = linked
+        .iter()
+        .flat_map(|names| names.iter().flatten())
+        .filter_map(|name| main.find_worktree(name).ok())
+        .filter(|worktree| worktree.validate().is_ok())
+        .filter_map(|worktree| Repository::open_from_worktree(&worktree).ok());
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .show(StatusShow::IndexAndWorkdir)
+        .include_untracked(false)
+        .exclude_submodules(true);
+
+    let mut dirty = 0;
+    for other in linked.chain(Repository::open(main.path()).ok()) {
+        let Some(workdir) = other.workdir().and_then(|dir| dir.canonicalize().ok()) else {
+            continue;
+        };
+        if Some(workdir) == current {
+            continue;
+        }
+        if other
+            .statuses(Some(&mut status_options))
+            .is_ok_and(|statuses| !statuses.is_empty())
+        {
+            dirty += 1;
+        }
+    }
+    dirty
+}
+
+pub fn read_identity(path: &Path) -> GitIdentityInfo {
+    let Ok(repository) = Repository::open(path) else {
+        return GitIdentityInfo {
+            email: None,
+            signing_key: None,
+            remote_url: None,
+        };
+    };
+
+    let config = repository.config().ok();
+    let get = |key: &str| config.as_ref().and_then(|c| c.get_string(key).ok());
+
+    GitIdentityInfo {
+        email: get("user.email"),
+        signing_key: get("user.signingkey"),
+        remote_url: default_remote_name(&repository)
+            .and_then(|name| repository.find_remote(&name).ok())
+            .and_then(|remote| remote.url().map(ToOwned::to_owned)),
+    }
+}
+
+pub fn read_forge_info(path: &Path) -> ForgeInfo {
+    let Ok(repository) = Repository::open(path) else {
+        return ForgeInfo::default();
+    };
+
+    let head = repository.head().ok();
+    // `branch.<name>.merge` is only meaningful alongside `branch.<name>.remote`.
+    let remote_branch = head
+        .as_ref()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand())
+        .filter(|branch| {
+            repository
+                .branch_upstream_remote(&format!("refs/heads/{}", branch))
+                .is_ok()
+        })
+        .and_then(|branch| {
+            let config = repository.config().ok()?;
+            config.get_string(&format!("branch.{}.merge", branch)).ok()
+        })
+        .map(|merge| merge.trim_start_matches("refs/heads/").to_owned());
+
+    let remote = default_remote_name(&repository);
+    let default_branch = remote.as_deref().and_then(|remote| {
+        let prefix = format!("refs/remotes/{}/", remote);
+        let reference = repository.find_reference(&format!("{}HEAD", prefix)).ok()?;
+        reference
+            .symbolic_target()?
+            .strip_prefix(&prefix)
+            .map(ToOwned::to_owned)
+    });
+
+    ForgeInfo {
+        branch: head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand())
+            .map(ToOwned::to_owned),
+        head: head.and_then(|head| head.target()).map(|id| id.to_string()),
+        remote_url: remote
+            .and_then(|name| repository.find_remote(&name).ok())
+            .and_then(|remote| remote.url().map(ToOwned::to_owned)),
+        remote_branch,
+        default_branch,
+    }
+}
+
+/// The remote `git fetch` would use with no arguments: the current branch's
+/// upstream remote, then `origin`, then the only remote if there's just one.
+fn default_remote_name(repository: &Repository) -> Option<String> {
+    let upstream = repository
+        .head()
+        .ok()
+        .and_then(|head| head.name().map(ToOwned::to_owned))
+        .and_then(|refname| repository.branch_upstream_remote(&refname).ok())
+        .and_then(|name| name.as_str().map(ToOwned::to_owned));
+    if upstream.is_some() {
+        return upstream;
+    }
+
+    let remotes = repository.remotes().ok()?;
+    let names: Vec<&str> = remotes.iter().flatten().collect();
+    match names.as_slice() {
+        [only] => Some(only.to_string()),
+        names if names.contains(&"origin") => Some("origin".to_string()),
+        _ => None,
     }
 }
@@ -1,7 +1,7 @@
 use std::path::Path;
 use std::process::Command;
 
-use super::GitStats;
+use super::{ForgeInfo, GitIdentityInfo, GitStats, StatsOptions, SubmoduleStats};
 
 pub fn get_first_number(s: &str) -> u32 {
     s.chars()
@@ -67,7 +67,9 @@ pub fn get_detached_branch_name() -> String {
     }
 }
 
-pub fn run_git(_: &Path) -> GitStats {
+pub fn run_git(path: &Path, options: StatsOptions) -> GitStats {
+    let submodule_paths = submodule_paths(path);
+
     let output = Command::new("git")
         .args(["status", "--porcelain", "-b"])
         .output()
@@ -120,10 +122,27 @@ pub fn run_git(_: &Path) -> GitStats {
             }
         };
     };
-    for op in lines.flat_map(|line| line.get(..2)) {
-        add_file(std::str::from_utf8(op).unwrap());
+    for line in lines.filter(|line| line.len() > 3) {
+        let entry = std::str::from_utf8(&line[..2]).unwrap();
+        // A submodule's working tree state is tracked separately in
+        // `SubmoduleStats`; only a staged gitlink change counts here.
+        let file = String::from_utf8_lossy(&line[3..]);
+        if submodule_paths.iter().any(|sm| *sm == file) {
+            add_file(&format!("{} ", &entry[..1]));
+        } else {
+            add_file(entry);
+        }
     }
 
+    let (sparse_checkout, partial_clone) = checkout_modes(
+        &git_query(&[
+            "config",
+            "--get-regexp",
+            r"^(core\.sparsecheckout|extensions\.partialclone|remote\..*\.promisor)$",
+        ])
+        .unwrap_or_default(),
+    );
+
     super::GitStats {
         untracked,
         ahead,
@@ -133,5 +152,266 @@ pub fn run_git(_: &Path) -> GitStats {
         conflicted,
         remote,
         branch_name,
+        submodules: if options.submodules {
+            submodule_stats(path, &submodule_paths)
+        } else {
+            SubmoduleStats::default()
+        },
+        sparse_checkout,
+        partial_clone,
+        dirty_worktrees: if options.worktrees {
+            dirty_worktrees(path)
+        } else {
+            0
+        },
+    }
+}
+
+/// Reads whether sparse-checkout and partial clone are active from the
+/// `key value` lines `git config --get-regexp` prints. Keys come back
+/// lowercased, and a key with no value is a boolean `true`.
+fn checkout_modes(config: &str) -> (bool, bool) {
+    let (mut sparse_checkout, mut partial_clone) = (false, false);
+    for line in config.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, "true"));
+        let enabled = matches!(value, "true" | "yes" | "on" | "1");
+        match key {
+            "core.sparsecheckout" => sparse_checkout = enabled,
+            "extensions.partialclone" => partial_clone = true,
+            _ if key.ends_with(".promisor") => partial_clone |= enabled,
+            _ => (),
+        }
+    }
+    (sparse_checkout, partial_clone)
+}
+
+/// Counts the repository's other worktrees (the main one included, when we're
+/// in a linked worktree) that have changes to tracked files.
+fn dirty_worktrees(repo_root: &Path) -> u32 {
+    let current = repo_root.canonicalize().ok();
+    let list = git_query(&["worktree", "list", "--porcelain"]).unwrap_or_default();
+
+    worktree_paths(&list)
+        .into_iter()
+        .map(Path::new)
+        .filter(|path| path.canonicalize().ok() != current && path.is_dir())
+        .filter(|path| {
+            Command::new("git")
+                .current_dir(path)
+                .args([
+                    "status",
+                    "--porcelain",
+                    "--untracked-files=no",
+                    "--ignore-submodules=all",
+                ])
+                .output()
+                .is_ok_and(|output| output.status.success() && !output.stdout.is_empty())
+        })
+        .count() as u32
+}
+
+/// The checked-out worktree paths in `git worktree list --porcelain` output.
+/// The main entry of a bare repository has no working tree and is skipped.
+fn worktree_paths(porcelain: &str) -> Vec<&str> {
+    porcelain
+        .split("\n\n")
+        .filter(|entry| !entry.lines().any(|line| line == "bare"))
+        .filter_map(|entry| {
+            entry
+                .lines()
+                .find_map(|line| line.strip_prefix("worktree "))
+        })
+        .collect()
+}
+
+/// Paths of the submodules declared in `.gitmodules`, relative to the repo root.
+fn submodule_paths(repo_root: &Path) -> Vec<String> {
+    if !repo_root.join(".gitmodules").is_file() {
+        return Vec::new();
+    }
+
+    Command::new("git")
+        .current_dir(repo_root)
+        .args([
+            "config",
+            "--file",
+            ".gitmodules",
+            "--get-regexp",
+            r"\.path$",
+        ])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_once(' ').map(|(_, path)| path.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn submodule_stats(repo_root: &Path, paths: &[String]) -> SubmoduleStats {
+    // An uninitialized submodule is just an empty directory: no `.git` inside.
+    let (initialized, uninitialized): (Vec<&String>, Vec<&String>) = paths
+        .iter()
+        .partition(|path| repo_root.join(path).join(".git").exists());
+
+    let mut stats = SubmoduleStats {
+        uninitialized: uninitialized.len() as u32,
+        ..Default::default()
+    };
+    if initialized.is_empty() {
+        return stats;
+    }
+
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .args(["status", "--porcelain=v2", "--ignore-submodules=none", "--"])
+        .args(initialized)
+        .output();
+    if let Ok(output) = output {
+        count_submodule_states(&String::from_utf8_lossy(&output.stdout), &mut stats);
+    }
+    stats
+}
+
+/// Tallies the `S<c><m><u>` submodule field of `git status --porcelain=v2`
+/// entries: `C` for new commits, `M` for modified and `U` for untracked content.
+fn count_submodule_states(porcelain_v2: &str, stats: &mut SubmoduleStats) {
+    for line in porcelain_v2.lines() {
+        let mut fields = line.split(' ');
+        if !matches!(fields.next(), Some("1" | "2" | "u")) {
+            continue;
+        }
+        let Some(sub) = fields.nth(1).and_then(|sub| sub.strip_prefix('S')) else {
+            continue;
+        };
+
+        let flags: Vec<char> = sub.chars().collect();
+        if let [commits, modified, untracked] = flags[..] {
+            stats.count(commits == 'C', modified == 'M', untracked == 'U', false);
+        }
+    }
+}
+
+/// Runs a read-only git query in the current directory, returning its trimmed
+/// stdout, or `None` when git fails or prints nothing (e.g. an unset key).
+fn git_query(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+pub fn read_identity(_: &Path) -> GitIdentityInfo {
+    GitIdentityInfo {
+        email: git_query(&["config", "--get", "user.email"]),
+        signing_key: git_query(&["config", "--get", "user.signingkey"]),
+        // With no remote argument this resolves the same default remote that a
+        // bare `git fetch` would use.
+        remote_url: git_query(&["ls-remote", "--get-url"]),
+    }
+}
+
+pub fn read_forge_info(_: &Path) -> ForgeInfo {
+    // Prints the commit id, then the full ref name, which is just `HEAD` when
+    // detached. Fails on an unborn branch.
+    let head = git_query(&["rev-parse", "HEAD", "--symbolic-full-name", "HEAD"]);
+    let (head, head_ref) = match head.as_deref().and_then(|head| head.split_once('\n')) {
+        Some((id, head_ref)) => (Some(id.to_string()), Some(head_ref)),
+        None => (None, None),
+    };
+
+    let upstream = head_ref
+        .filter(|head_ref| head_ref.starts_with("refs/heads/"))
+        .and_then(|head_ref| {
+            git_query(&[
+                "for-each-ref",
+                "--format=%(upstream:remotename) %(upstream:remoteref)",
+                head_ref,
+            ])
+        });
+    let (upstream_remote, remote_branch) = match upstream.as_deref().map(|u| u.split_once(' ')) {
+        Some(Some((remote, merge))) => (
+            Some(remote.to_string()),
+            Some(merge.trim_start_matches("refs/heads/").to_string()),
+        ),
+        _ => (None, None),
+    };
+
+    // `ls-remote --get-url` with no argument resolves the default remote, but
+    // its name is needed for the default branch, so assume `origin` there.
+    let remote = upstream_remote.as_deref().unwrap_or("origin");
+    let prefix = format!("refs/remotes/{}/", remote);
+    let default_branch = git_query(&["symbolic-ref", "-q", &format!("{}HEAD", prefix)])
+        .and_then(|target| target.strip_prefix(&prefix).map(ToOwned::to_owned));
+
+    let mut get_url = vec!["ls-remote", "--get-url"];
+    get_url.extend(upstream_remote.as_deref());
+
+    ForgeInfo {
+        head,
+        branch: head_ref
+            .and_then(|head_ref| head_ref.strip_prefix("refs/heads/"))
+            .map(ToOwned::to_owned),
+        remote_url: git_query(&get_url),
+        remote_branch,
+        default_branch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checkout_modes, count_submodule_states, worktree_paths, SubmoduleStats};
+
+    #[test]
+    fn counts_each_kind_of_submodule_dirtiness() {
+        let porcelain = "\
+1 .M SC.. 160000 160000 160000 abc abc vendor/a
+1 .M S.M. 160000 160000 160000 abc abc vendor/b
+1 .M S.MU 160000 160000 160000 abc abc vendor/c
+1 .M N... 100644 100644 100644 abc abc src/lib.rs
+? untracked.txt
+";
+        let mut stats = SubmoduleStats::default();
+        count_submodule_states(porcelain, &mut stats);
+
+        assert_eq!(stats.new_commits, 1);
+        assert_eq!(stats.modified, 2);
+        assert_eq!(stats.untracked, 1);
+        assert_eq!(stats.uninitialized, 0);
+    }
+
+    #[test]
+    fn reads_sparse_and_partial_clone_config() {
+        assert_eq!(checkout_modes(""), (false, false));
+        assert_eq!(checkout_modes("core.sparsecheckout true\n"), (true, false));
+        assert_eq!(
+            checkout_modes("extensions.partialclone origin\n"),
+            (false, true)
+        );
+        assert_eq!(
+            checkout_modes("core.sparsecheckout false\nremote.origin.promisor\n"),
+            (false, true)
+        );
+    }
+
+    #[test]
+    fn lists_worktrees_with_a_checkout() {
+        let porcelain = "\
+worktree /srv/repo.git
+bare
+
+worktree /src/feature-x
+HEAD 1234
+branch refs/heads/feature-x
+
+worktree /src/hotfix
+HEAD 5678
+detached
+";
+        assert_eq!(worktree_paths(porcelain), ["/src/feature-x", "/src/hotfix"]);
     }
 }
@@ -0,0 +1,131 @@
+use std::marker::PhantomData;
+
+use regex::Regex;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::forge::encode_ref;
+use super::{find_git_dir, internal, parse_remote, Module};
+
+/// Shows the ticket the current branch is named after (`PROJ-123` in
+/// `feature/PROJ-123-login`), linked to it on the issue tracker.
+pub struct GitTicket<S> {
+    pattern: Regex,
+    url: String,
+    scheme: PhantomData<S>,
+}
+
+pub trait GitTicketScheme: DefaultColors {
+    fn git_ticket_fg() -> Color {
+        Self::default_fg()
+    }
+    fn git_ticket_bg() -> Color {
+        Self::default_bg()
+    }
+
+    const TICKET_SYMBOL: &'static str = "\u{f145}";
+}
+
+impl<S: GitTicketScheme> GitTicket<S> {
+    pub fn new(pattern: Regex, url: String) -> GitTicket<S> {
+        GitTicket {
+            pattern,
+            url,
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<S: GitTicketScheme> Module for GitTicket<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some((git_dir, _)) = find_git_dir() else {
+            return;
+        };
+
+        let info = internal::read_forge_info(&git_dir);
+        let Some(key) = info
+            .branch
+            .as_deref()
+            .and_then(|b| ticket_key(&self.pattern, b))
+        else {
+            return;
+        };
+
+        let label = format!("{} {}", S::TICKET_SYMBOL, key);
+        let style = Style::simple(S::git_ticket_fg(), S::git_ticket_bg());
+        match ticket_url(&self.url, key, info.remote_url.as_deref()) {
+            Some(url) => powerline.add_hyperlink_segment(&label, &url, style, &[]),
+            None => powerline.add_segment(label, style),
+        }
+    }
+}
+
+/// The pattern's first capture group, or the whole match when it has none, so
+/// `#(\d+)` gives `123` for `fix/#123` while `[A-Z]+-\d+` gives `PROJ-7`.
+fn ticket_key<'a>(pattern: &Regex, branch: &'a str) -> Option<&'a str> {
+    let captures = pattern.captures(branch)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str())
+}
+
+/// Fills in `{key}` (percent-encoded), plus `{host}` and `{repo}` from the
+/// remote for trackers that live next to the code. `None` when those are
+/// needed but there's no remote to take them from.
+fn ticket_url(template: &str, key: &str, remote_url: Option<&str>) -> Option<String> {
+    let url = template.replace("{key}", &encode_ref(key));
+    if !url.contains("{host}") && !url.contains("{repo}") {
+        return Some(url);
+    }
+    let (host, repo) = remote_url.and_then(parse_remote)?;
+    Some(url.replace("{host}", host).replace("{repo}", repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_the_key_from_the_branch() {
+        let jira = Regex::new(r"([A-Z]+-\d+)").unwrap();
+        assert_eq!(
+            ticket_key(&jira, "feature/PROJ-123-login"),
+            Some("PROJ-123")
+        );
+        assert_eq!(ticket_key(&jira, "main"), None);
+
+        let issue = Regex::new(r"#(\d+)").unwrap();
+        assert_eq!(ticket_key(&issue, "fix/#42-crash"), Some("42"));
+
+        let whole = Regex::new(r"[A-Z]+-\d+").unwrap();
+        assert_eq!(ticket_key(&whole, "OPS-7"), Some("OPS-7"));
+    }
+
+    #[test]
+    fn fills_in_the_url_template() {
+        assert_eq!(
+            ticket_url("https://corp.atlassian.net/browse/{key}", "PROJ-1", None).as_deref(),
+            Some("https://corp.atlassian.net/browse/PROJ-1")
+        );
+        assert_eq!(
+            ticket_url(
+                "https://{host}/{repo}/issues/{key}",
+                "42",
+                Some("git@github.com:owner/repo.git")
+            )
+            .as_deref(),
+            Some("https://github.com/owner/repo/issues/42")
+        );
+        assert_eq!(
+            ticket_url("https://{host}/{repo}/issues/{key}", "42", None),
+            None
+        );
+        assert_eq!(
+            ticket_url("https://tracker/issues?id={key}", "a&b c", None).as_deref(),
+            Some("https://tracker/issues?id=a%26b%20c")
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::output_with_timeout;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// How long cached incoming/outgoing counts stay fresh. They need a round trip
+/// to the default path, so they're refreshed far less often than the status.
+const REMOTE_TTL: Duration = Duration::from_secs(300);
+const REFRESH_DEBOUNCE: Duration = Duration::from_secs(60);
+
+/// The Mercurial counterpart of `Git`: the active bookmark (or branch), the
+/// working directory's modified/added/removed/unknown counts, and the
+/// outgoing/incoming changesets once a background lookup has cached them.
+pub struct Hg<S> {
+    /// Upper bound on the `hg status` call. Past it only the branch is shown.
+    timeout: Duration,
+    /// Whether to look up incoming/outgoing changesets in the background.
+    remote: bool,
+    scheme: PhantomData<S>,
+}
+
+pub trait HgScheme: DefaultColors {
+    fn hg_clean_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_clean_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_dirty_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_dirty_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_modified_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_modified_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_added_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_added_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_removed_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_removed_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_unknown_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_unknown_fg() -> Color {
+        Self::default_fg()
+    }
+    fn hg_remote_bg() -> Color {
+        Self::default_bg()
+    }
+    fn hg_remote_fg() -> Color {
+        Self::default_fg()
+    }
+
+    const HG_SYMBOL: &'static str = "\u{e7a3}";
+    const BOOKMARK_SYMBOL: &'static str = "\u{f02e}";
+    const MODIFIED_SYMBOL: &'static str = "\u{eae9}";
+    const ADDED_SYMBOL: &'static str = "+";
+    const REMOVED_SYMBOL: &'static str = "-";
+    const UNKNOWN_SYMBOL: &'static str = "?";
+    const OUTGOING_SYMBOL: &'static str = "\u{f062}";
+    const INCOMING_SYMBOL: &'static str = "\u{f063}";
+}
+
+impl<S: HgScheme> Hg<S> {
+    pub fn new(timeout: Duration, remote: bool) -> Hg<S> {
+        Hg {
+            timeout,
+            remote,
+            scheme: PhantomData,
+        }
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
+struct HgStatus {
+    /// `M`, plus `!` (deleted without `hg remove`), like git's unstaged count.
+    modified: u32,
+    added: u32,
+    removed: u32,
+    unknown: u32,
+}
+
+impl HgStatus {
+    fn is_dirty(&self) -> bool {
+        (self.modified + self.added + self.removed + self.unknown) > 0
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HgRemoteCache {
+    /// `None` when the lookup failed, e.g. no default path or no network.
+    outgoing: Option<u32>,
+    incoming: Option<u32>,
+    fetched_at: u64,
+}
+
+impl<S: HgScheme> Module for Hg<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some(root) = find_hg_root() else {
+            return;
+        };
+
+        let status = output_with_timeout(hg(&root).args(["status", "--color=never"]), self.timeout)
+            .map(|output| parse_status(&output));
+
+        let (fg, bg) = if status.as_ref().is_some_and(HgStatus::is_dirty) {
+            (S::hg_dirty_fg(), S::hg_dirty_bg())
+        } else {
+            (S::hg_clean_fg(), S::hg_clean_bg())
+        };
+        let label = match read_bookmark(&root) {
+            Some(bookmark) => format!("{} {} {}", S::HG_SYMBOL, S::BOOKMARK_SYMBOL, bookmark),
+            None => format!("{} {}", S::HG_SYMBOL, read_branch(&root)),
+        };
+        powerline.add_segment(label, Style::simple(fg, bg));
+
+        if let Some(status) = status {
+            for (count, symbol, fg, bg) in [
+                (
+                    status.modified,
+                    S::MODIFIED_SYMBOL,
+                    S::hg_modified_fg(),
+                    S::hg_modified_bg(),
+                ),
+                (
+                    status.unknown,
+                    S::UNKNOWN_SYMBOL,
+                    S::hg_unknown_fg(),
+                    S::hg_unknown_bg(),
+                ),
+                (
+                    status.added,
+                    S::ADDED_SYMBOL,
+                    S::hg_added_fg(),
+                    S::hg_added_bg(),
+                ),
+                (
+                    status.removed,
+                    S::REMOVED_SYMBOL,
+                    S::hg_removed_fg(),
+                    S::hg_removed_bg(),
+                ),
+            ] {
+                if count > 0 {
+                    powerline.add_segment(format!("{} {}", count, symbol), Style::simple(fg, bg));
+                }
+            }
+        }
+
+        if self.remote {
+            self.append_remote(powerline, &root);
+        }
+    }
+}
+
+impl<S: HgScheme> Hg<S> {
+    fn append_remote(&self, powerline: &mut Powerline, root: &Path) {
+        let Some(cache_path) = cache::cache_path("hg", root) else {
+            return;
+        };
+
+        let cache = cache::read::<HgRemoteCache>(&cache_path);
+        if cache
+            .as_ref()
+            .is_none_or(|c| cache::is_stale(c.fetched_at, REMOTE_TTL))
+        {
+            cache::spawn_refresh(
+                &cache_path,
+                REFRESH_DEBOUNCE,
+                [
+                    "refresh-hg".as_ref(),
+                    "--repo-dir".as_ref(),
+                    root.as_os_str(),
+                    "--cache".as_ref(),
+                    cache_path.as_os_str(),
+                ],
+            );
+        }
+
+        let Some(cache) = cache else {
+            return;
+        };
+        let mut remote = String::new();
+        if let Some(outgoing) = cache.outgoing.filter(|&n| n > 0) {
+            let _ = write!(remote, "{}{}", outgoing, S::OUTGOING_SYMBOL);
+        }
+        if let Some(incoming) = cache.incoming.filter(|&n| n > 0) {
+            if !remote.is_empty() {
+                remote.push(' ');
+            }
+            let _ = write!(remote, "{}{}", incoming, S::INCOMING_SYMBOL);
+        }
+
+        if !remote.is_empty() {
+            powerline.add_segment(remote, Style::simple(S::hg_remote_fg(), S::hg_remote_bg()));
+        }
+    }
+}
+
+/// An `hg` invocation in `root` with `HGPLAIN` set, so user config such as
+/// aliases, pagers or a localised UI can't change the output we parse.
+fn hg(root: &Path) -> Command {
+    let mut command = Command::new("hg");
+    command.current_dir(root).env("HGPLAIN", "1");
+    command
+}
+
+/// The repository root containing the current directory, found by its `.hg`.
+fn find_hg_root() -> Option<PathBuf> {
+    let mut dir = env::current_dir().ok()?;
+    loop {
+        if dir.join(".hg").is_dir() {
+            return Some(dir);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// The active bookmark, which Mercurial keeps in `.hg/bookmarks.current`.
+fn read_bookmark(root: &Path) -> Option<String> {
+    let bookmark = fs::read_to_string(root.join(".hg").join("bookmarks.current")).ok()?;
+    let bookmark = bookmark.trim();
+    (!bookmark.is_empty()).then(|| bookmark.to_string())
+}
+
+/// The working directory's named branch; `.hg/branch` is absent on `default`.
+fn read_branch(root: &Path) -> String {
+    fs::read_to_string(root.join(".hg").join("branch"))
+        .ok()
+        .map(|branch| branch.trim().to_string())
+        .filter(|branch| !branch.is_empty())
+        .unwrap_or_else(|| String::from("default"))
+}
+
+fn parse_status(output: &str) -> HgStatus {
+    let mut status = HgStatus::default();
+    for line in output.lines() {
+        match line.as_bytes().first() {
+            Some(b'M' | b'!') => status.modified += 1,
+            Some(b'A') => status.added += 1,
+            Some(b'R') => status.removed += 1,
+            Some(b'?') => status.unknown += 1,
+            _ => (),
+        }
+    }
+    status
+}
+
+/// Counts the outgoing and incoming changesets against the default path and
+/// writes the cache. Invoked by the hidden `refresh-hg` subcommand from the
+/// detached process spawned above.
+pub fn refresh_hg(root: &Path, cache_path: &Path) {
+    let cache = HgRemoteCache {
+        outgoing: count_changesets(root, "outgoing"),
+        incoming: count_changesets(root, "incoming"),
+        fetched_at: cache::now_secs(),
+    };
+
+    cache::write(cache_path, &cache);
+    cache::finish_refresh(cache_path);
+}
+
+fn count_changesets(root: &Path, direction: &str) -> Option<u32> {
+    // `--noninteractive` answers any prompt (e.g. for credentials) with its default instead
+    // of waiting on a terminal that isn't there.
+    let output = hg(root)
+        .args([direction, "--quiet", "--noninteractive"])
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    // Exit status 1 means "nothing to transfer"; anything else is an error.
+    match output.status.code() {
+        Some(0) => Some(String::from_utf8_lossy(&output.stdout).lines().count() as u32),
+        Some(1) => Some(0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_status, HgStatus};
+
+    #[test]
+    fn counts_each_status_code() {
+        let output = "M src/lib.rs\nM README\n! gone.txt\nA new.rs\nR old.rs\n? notes.txt\n";
+        assert_eq!(
+            parse_status(output),
+            HgStatus {
+                modified: 3,
+                added: 1,
+                removed: 1,
+                unknown: 1,
+            }
+        );
+        assert!(!parse_status("").is_dirty());
+    }
+}
@@ -0,0 +1,304 @@
+use std::env;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// Fallback refresh interval, which bounds how long edits made outside jj take
+/// to show. Most changes are picked up sooner, as every jj command moves the
+/// operation log head, which invalidates the cache at once.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const REFRESH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Prints one line per revision in `@ | @-`: whether it's the working copy,
+/// the shortest unique change id prefix, local bookmarks, and whether it's
+/// empty or conflicted. Fields are joined with `++` rather than `separate()`,
+/// which would drop an empty bookmark list and shift the columns.
+const LOG_TEMPLATE: &str = r#"if(current_working_copy, "@", "-") ++ "\t"
+  ++ change_id.shortest(8) ++ "\t"
+  ++ local_bookmarks.map(|b| b.name()).join(",") ++ "\t"
+  ++ if(empty, "empty", "changed") ++ "\t"
+  ++ if(conflict, "conflict", "ok") ++ "\n""#;
+
+/// Shows the Jujutsu working-copy change: its change id, the bookmarks on it
+/// (or on its parent), and whether it has changes or conflicts. Also covers
+/// repos colocated with git, where the `Git` segment steps aside when it
+/// shares a line with this one.
+pub struct Jj<S> {
+    scheme: PhantomData<S>,
+}
+
+pub trait JjScheme: DefaultColors {
+    fn jj_clean_fg() -> Color {
+        Self::default_fg()
+    }
+    fn jj_clean_bg() -> Color {
+        Self::default_bg()
+    }
+    fn jj_changed_fg() -> Color {
+        Self::default_fg()
+    }
+    fn jj_changed_bg() -> Color {
+        Self::default_bg()
+    }
+    fn jj_conflict_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn jj_conflict_bg() -> Color {
+        Self::alert_bg()
+    }
+
+    const JJ_SYMBOL: &'static str = "\u{f1d3}";
+    const BOOKMARK_SYMBOL: &'static str = "\u{f02e}";
+    const CHANGED_SYMBOL: &'static str = "\u{eae9}";
+    const CONFLICT_SYMBOL: &'static str = "\u{273C}";
+}
+
+impl<S: JjScheme> Default for Jj<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: JjScheme> Jj<S> {
+    pub fn new() -> Jj<S> {
+        Jj {
+            scheme: PhantomData,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JjCache {
+    /// The operation log head(s) the status was read at. When jj moves on, the
+    /// entry is outdated regardless of its age.
+    op_heads: String,
+    status: Option<JjStatus>,
+    fetched_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct JjStatus {
+    change_id: String,
+    /// Local bookmarks on `@`, or on its parent(s) when `@` has none - the
+    /// usual state while working on top of a bookmark.
+    bookmarks: Vec<String>,
+    has_changes: bool,
+    conflict: bool,
+}
+
+impl<S: JjScheme> Module for Jj<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Some(workspace) = find_jj_workspace() else {
+            return;
+        };
+        let Some(cache_path) = cache::cache_path("jj", &workspace) else {
+            return;
+        };
+
+        let op_heads = read_op_heads(&workspace).unwrap_or_default();
+        let cache = cache::read::<JjCache>(&cache_path);
+
+        if cache
+            .as_ref()
+            .is_none_or(|c| c.op_heads != op_heads || cache::is_stale(c.fetched_at, CACHE_TTL))
+        {
+            cache::spawn_refresh(
+                &cache_path,
+                REFRESH_DEBOUNCE,
+                [
+                    "refresh-jj".as_ref(),
+                    "--repo-dir".as_ref(),
+                    workspace.as_os_str(),
+                    "--cache".as_ref(),
+                    cache_path.as_os_str(),
+                ],
+            );
+        }
+
+        // Render whatever we have right now (possibly one operation behind).
+        let Some(JjCache {
+            status: Some(status),
+            ..
+        }) = cache
+        else {
+            return;
+        };
+
+        let (fg, bg) = if status.conflict {
+            (S::jj_conflict_fg(), S::jj_conflict_bg())
+        } else if status.has_changes {
+            (S::jj_changed_fg(), S::jj_changed_bg())
+        } else {
+            (S::jj_clean_fg(), S::jj_clean_bg())
+        };
+
+        let mut label = format!("{} {}", S::JJ_SYMBOL, status.change_id);
+        if !status.bookmarks.is_empty() {
+            label = format!(
+                "{} {} {}",
+                label,
+                S::BOOKMARK_SYMBOL,
+                status.bookmarks.join(" ")
+            );
+        }
+        if status.has_changes {
+            label = format!("{} {}", label, S::CHANGED_SYMBOL);
+        }
+        if status.conflict {
+            label = format!("{} {}", label, S::CONFLICT_SYMBOL);
+        }
+
+        powerline.add_segment(label, Style::simple(fg, bg));
+    }
+}
+
+/// The root of the jj workspace containing the current directory, found by
+/// its `.jj` directory. Colocated repos have a `.git` next to it.
+fn find_jj_workspace() -> Option<PathBuf> {
+    let mut dir = env::current_dir().ok()?;
+    loop {
+        if dir.join(".jj").is_dir() {
+            return Some(dir);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// The current operation log head ids. Secondary workspaces store a `repo`
+/// file pointing at the main workspace's store instead of a directory.
+fn read_op_heads(workspace: &Path) -> Option<String> {
+    let mut repo = workspace.join(".jj").join("repo");
+    if repo.is_file() {
+        repo = workspace
+            .join(".jj")
+            .join(fs::read_to_string(&repo).ok()?.trim());
+    }
+
+    let mut heads: Vec<String> = fs::read_dir(repo.join("op_heads").join("heads"))
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect();
+    heads.sort();
+    Some(heads.join(","))
+}
+
+/// Runs `jj log` and writes the cache. Invoked by the hidden `refresh-jj`
+/// subcommand from the detached process spawned above.
+pub fn refresh_jj(workspace: &Path, cache_path: &Path) {
+    let status = fetch_status(workspace);
+    // Read after the log, so the operation recording a snapshot it took
+    // doesn't outdate the entry straight away.
+    let cache = JjCache {
+        op_heads: read_op_heads(workspace).unwrap_or_default(),
+        status,
+        fetched_at: cache::now_secs(),
+    };
+
+    cache::write(cache_path, &cache);
+    cache::finish_refresh(cache_path);
+}
+
+fn fetch_status(workspace: &Path) -> Option<JjStatus> {
+    // Without `--ignore-working-copy`, so edits made since the last jj command
+    // are snapshotted and counted. This runs in the background refresh, never
+    // while the prompt waits.
+    let output = Command::new("jj")
+        .current_dir(workspace)
+        .args([
+            "log",
+            "--no-graph",
+            "--color=never",
+            "-r",
+            "@ | @-",
+            "-T",
+            LOG_TEMPLATE,
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_log(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_log(log: &str) -> Option<JjStatus> {
+    let mut working_copy = None;
+    let mut parent_bookmarks = Vec::new();
+
+    for line in log.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [marker, change_id, bookmarks, emptiness, conflict] = fields[..] else {
+            continue;
+        };
+        let bookmarks = bookmarks
+            .split(',')
+            .filter(|b| !b.is_empty())
+            .map(ToOwned::to_owned);
+
+        if marker == "@" {
+            working_copy = Some(JjStatus {
+                change_id: change_id.to_string(),
+                bookmarks: bookmarks.collect(),
+                has_changes: emptiness == "changed",
+                conflict: conflict == "conflict",
+            });
+        } else {
+            parent_bookmarks.extend(bookmarks);
+        }
+    }
+
+    let mut status = working_copy?;
+    if status.bookmarks.is_empty() {
+        status.bookmarks = parent_bookmarks;
+    }
+    Some(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_working_copy_and_parent_bookmarks() {
+        let log = "@\tqpvuntsm\t\tchanged\tok\n-\tzzzzzzzz\tmain,release\tempty\tok\n";
+        assert_eq!(
+            parse_log(log),
+            Some(JjStatus {
+                change_id: "qpvuntsm".into(),
+                bookmarks: vec!["main".into(), "release".into()],
+                has_changes: true,
+                conflict: false,
+            })
+        );
+    }
+
+    #[test]
+    fn working_copy_bookmarks_win_over_the_parent() {
+        let log = "-\tzzzzzzzz\tmain\tempty\tok\n@\tkkmpptxz\tfeature\tempty\tconflict\n";
+        let status = parse_log(log).unwrap();
+        assert_eq!(status.bookmarks, ["feature"]);
+        assert!(!status.has_changes);
+        assert!(status.conflict);
+    }
+
+    #[test]
+    fn missing_working_copy_yields_nothing() {
+        assert_eq!(parse_log(""), None);
+        assert_eq!(parse_log("-\tzzzzzzzz\tmain\tempty\tok\n"), None);
+    }
+}
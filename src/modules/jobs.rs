@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the shell's background jobs, `pushd` stack depth and how deeply it's
+/// nested in other shells, each only when there's any.
+pub struct Jobs<S> {
+    jobs: usize,
+    dirs: usize,
+    scheme: PhantomData<S>,
+}
+
+pub trait JobsScheme: DefaultColors {
+    fn jobs_fg() -> Color {
+        Self::default_fg()
+    }
+    fn jobs_bg() -> Color {
+        Self::default_bg()
+    }
+}
+
+impl<S: JobsScheme> Jobs<S> {
+    /// `jobs` and `dirs` come from the shell, through `superline show`'s
+    /// `--jobs` and `--dirs`.
+    pub fn new(jobs: usize, dirs: usize) -> Jobs<S> {
+        Jobs {
+            jobs,
+            dirs,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const JOBS_ICON: &str = "\u{f013}"; // nf-fa-gear
+const DIRS_ICON: &str = "\u{f0254}"; // nf-md-folder_multiple
+const SHELL_ICON: &str = "\u{f489}"; // nf-oct-terminal
+
+impl<S: JobsScheme> Module for Jobs<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+        if let Some(label) = label(self.jobs, self.dirs, nesting(&env)) {
+            powerline.add_segment(label, Style::simple(S::jobs_fg(), S::jobs_bg()));
+        }
+    }
+}
+
+/// The non-zero counts with their icons, or `None` when they're all zero.
+fn label(jobs: usize, dirs: usize, shells: usize) -> Option<String> {
+    let parts: Vec<String> = [(JOBS_ICON, jobs), (DIRS_ICON, dirs), (SHELL_ICON, shells)]
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(icon, count)| format!("{} {}", icon, count))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// How many shells this one is nested in. Each shell bumps `SHLVL` as it
+/// starts, so the outermost one sees `1`.
+fn nesting(env: &EnvGetter) -> usize {
+    env("SHLVL")
+        .and_then(|level| level.trim().parse::<usize>().ok())
+        .map_or(0, |level| level.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_only_the_counts_there_are() {
+        assert_eq!(label(0, 0, 0), None);
+        assert_eq!(label(2, 0, 0), Some(format!("{} 2", JOBS_ICON)));
+        assert_eq!(label(0, 3, 0), Some(format!("{} 3", DIRS_ICON)));
+        assert_eq!(label(0, 0, 1), Some(format!("{} 1", SHELL_ICON)));
+        assert_eq!(
+            label(1, 0, 2),
+            Some(format!("{} 1 {} 2", JOBS_ICON, SHELL_ICON))
+        );
+        assert_eq!(
+            label(1, 4, 2),
+            Some(format!("{} 1 {} 4 {} 2", JOBS_ICON, DIRS_ICON, SHELL_ICON))
+        );
+    }
+
+    #[test]
+    fn counts_nested_shells_from_shlvl() {
+        assert_eq!(nesting(&|_| None), 0);
+        assert_eq!(nesting(&|_| Some("1".to_string())), 0);
+        assert_eq!(nesting(&|_| Some("3".to_string())), 2);
+        assert_eq!(nesting(&|_| Some("0".to_string())), 0);
+        assert_eq!(nesting(&|_| Some("garbage".to_string())), 0);
+    }
+}
@@ -0,0 +1,185 @@
+use std::env;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use yaml_rust2::YamlLoader;
+
+use crate::colors::Color;
+use crate::config::KubeAlias;
+use crate::themes::DefaultColors;
+use crate::utils::glob_match;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// Shows the current kubectl context and its namespace, read straight from the
+/// kubeconfig files rather than by running `kubectl`.
+pub struct Kube<S> {
+    aliases: Vec<KubeAlias>,
+    /// Glob patterns for the contexts to show in the alert colours.
+    production: Vec<String>,
+    show_namespace: bool,
+    scheme: PhantomData<S>,
+}
+
+pub trait KubeScheme: DefaultColors {
+    fn kube_fg() -> Color {
+        Self::default_fg()
+    }
+    fn kube_bg() -> Color {
+        Self::default_bg()
+    }
+    fn kube_production_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn kube_production_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: KubeScheme> Kube<S> {
+    pub fn new(aliases: Vec<KubeAlias>, production: Vec<String>, show_namespace: bool) -> Kube<S> {
+        Kube {
+            aliases,
+            production,
+            show_namespace,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const KUBE_ICON: &str = "\u{f10fe}"; // nf-md-kubernetes
+
+impl<S: KubeScheme> Module for Kube<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let configs: Vec<String> = kubeconfig_paths()
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .collect();
+        let Some(context) = current_context(&configs) else {
+            return;
+        };
+
+        let name = self
+            .aliases
+            .iter()
+            .find(|alias| glob_match(&alias.context, &context.name))
+            .map_or(context.name.as_str(), |alias| alias.alias.as_str());
+        let production = self
+            .production
+            .iter()
+            .any(|pattern| glob_match(pattern, &context.name) || glob_match(pattern, name));
+
+        let label = match context.namespace.filter(|_| self.show_namespace) {
+            Some(namespace) => format!("{} {}:{}", KUBE_ICON, name, namespace),
+            None => format!("{} {}", KUBE_ICON, name),
+        };
+        let style = if production {
+            Style::simple(S::kube_production_fg(), S::kube_production_bg())
+        } else {
+            Style::simple(S::kube_fg(), S::kube_bg())
+        };
+        powerline.add_segment(label, style);
+    }
+}
+
+/// The files kubectl reads: those listed in `KUBECONFIG`, or `~/.kube/config`.
+fn kubeconfig_paths() -> Vec<PathBuf> {
+    match env::var_os("KUBECONFIG").filter(|paths| !paths.is_empty()) {
+        Some(paths) => env::split_paths(&paths)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect(),
+        None => crate::platform::home_dir()
+            .map(|home| home.join(".kube").join("config"))
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct KubeContext {
+    name: String,
+    namespace: Option<String>,
+}
+
+/// Merges the kubeconfig files the way kubectl does: the first file to set
+/// `current-context` picks it, and the first definition of that context wins.
+/// A context that isn't defined anywhere is still shown, without a namespace.
+fn current_context(configs: &[String]) -> Option<KubeContext> {
+    let docs: Vec<_> = configs
+        .iter()
+        .filter_map(|config| YamlLoader::load_from_str(config).ok())
+        .filter_map(|docs| docs.into_iter().next())
+        .collect();
+
+    let name = docs
+        .iter()
+        .filter_map(|doc| doc["current-context"].as_str())
+        .find(|name| !name.is_empty())?;
+    let namespace = docs
+        .iter()
+        .filter_map(|doc| doc["contexts"].as_vec())
+        .flatten()
+        .find(|context| context["name"].as_str() == Some(name))
+        .and_then(|context| context["context"]["namespace"].as_str())
+        .filter(|namespace| !namespace.is_empty())
+        .map(ToOwned::to_owned);
+
+    Some(KubeContext {
+        name: name.to_string(),
+        namespace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current_context, KubeContext};
+
+    #[test]
+    fn merges_kubeconfig_files_like_kubectl() {
+        let main = "
+apiVersion: v1
+kind: Config
+contexts:
+- context:
+    cluster: dev
+    user: me
+  name: dev
+- name: arn:aws:eks:eu-west-1:123456789012:cluster/prod
+  context:
+    cluster: prod
+    namespace: payments
+current-context: arn:aws:eks:eu-west-1:123456789012:cluster/prod
+"
+        .to_string();
+        // Only the first file's `current-context` counts, and the first
+        // definition of a context wins.
+        let extra = "
+contexts:
+- name: dev
+  context: {cluster: dev, namespace: staging}
+current-context: dev
+"
+        .to_string();
+
+        assert_eq!(
+            current_context(&[main.clone(), extra.clone()]),
+            Some(KubeContext {
+                name: "arn:aws:eks:eu-west-1:123456789012:cluster/prod".to_string(),
+                namespace: Some("payments".to_string()),
+            })
+        );
+        assert_eq!(
+            current_context(&[extra, main]),
+            Some(KubeContext {
+                name: "dev".to_string(),
+                namespace: Some("staging".to_string()),
+            })
+        );
+        assert_eq!(
+            current_context(&["current-context: \"\"".to_string()]),
+            None
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// Shows the 1-minute load average from `/proc/loadavg` once it reaches a
+/// threshold, by default the number of CPUs.
+pub struct Load<S> {
+    above: f64,
+    scheme: PhantomData<S>,
+}
+
+pub trait LoadScheme: DefaultColors {
+    fn load_fg() -> Color {
+        Self::default_fg()
+    }
+    fn load_bg() -> Color {
+        Self::default_bg()
+    }
+}
+
+impl<S: LoadScheme> Load<S> {
+    pub fn new(above: Option<f64>) -> Load<S> {
+        let cpus = std::thread::available_parallelism().map_or(1, |cpus| cpus.get());
+        Load {
+            above: above.unwrap_or(cpus as f64),
+            scheme: PhantomData,
+        }
+    }
+}
+
+const LOAD_ICON: &str = "\u{f04c5}"; // nf-md-speedometer
+
+impl<S: LoadScheme> Module for Load<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        match load_average(Path::new("/proc")) {
+            Some(load) if load >= self.above => powerline.add_segment(
+                format!("{} {:.2}", LOAD_ICON, load),
+                Style::simple(S::load_fg(), S::load_bg()),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// The first of `loadavg`'s three averages.
+fn load_average(proc_root: &Path) -> Option<f64> {
+    let loadavg = fs::read_to_string(proc_root.join("loadavg")).ok()?;
+    loadavg.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn reads_the_one_minute_average() {
+        let root = TempDir::new("load");
+        assert_eq!(load_average(&root), None);
+
+        fs::write(root.join("loadavg"), "3.42 2.10 1.05 4/1234 56789\n").unwrap();
+        assert_eq!(load_average(&root), Some(3.42));
+
+        fs::write(root.join("loadavg"), "0.00 0.01 0.05 1/99 42\n").unwrap();
+        assert_eq!(load_average(&root), Some(0.0));
+
+        fs::write(root.join("loadavg"), "").unwrap();
+        assert_eq!(load_average(&root), None);
+        fs::write(root.join("loadavg"), "busy\n").unwrap();
+        assert_eq!(load_average(&root), None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::{Powerline, Style};
+
+use super::Module;
+
+/// Shows how much of the memory is in use, from `/proc/meminfo`, once it
+/// reaches a threshold.
+pub struct Memory<S> {
+    /// In percent.
+    above: u8,
+    scheme: PhantomData<S>,
+}
+
+pub trait MemoryScheme: DefaultColors {
+    fn memory_fg() -> Color {
+        Self::default_fg()
+    }
+    fn memory_bg() -> Color {
+        Self::default_bg()
+    }
+}
+
+impl<S: MemoryScheme> Memory<S> {
+    pub fn new(above: u8) -> Memory<S> {
+        Memory {
+            above,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const MEMORY_ICON: &str = "\u{f035b}"; // nf-md-memory
+
+impl<S: MemoryScheme> Module for Memory<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        match used_percent(Path::new("/proc")) {
+            Some(used) if used >= self.above => powerline.add_segment(
+                format!("{} {}%", MEMORY_ICON, used),
+                Style::simple(S::memory_fg(), S::memory_bg()),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// The share of memory that isn't available to new programs. Page cache the
+/// kernel would give up counts as available, which `MemAvailable` accounts
+/// for; older kernels without it get the classic free + buffers + cached.
+fn used_percent(proc_root: &Path) -> Option<u8> {
+    let meminfo = fs::read_to_string(proc_root.join("meminfo")).ok()?;
+    let field = |name: &str| -> Option<u64> {
+        meminfo.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse().ok()
+        })
+    };
+
+    let total = field("MemTotal").filter(|&total| total > 0)?;
+    let available = field("MemAvailable").or_else(|| {
+        Some(field("MemFree")? + field("Buffers").unwrap_or(0) + field("Cached").unwrap_or(0))
+    })?;
+    Some((total.saturating_sub(available) * 100 / total) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn works_out_the_used_share() {
+        let root = TempDir::new("memory");
+        fs::write(
+            root.join("meminfo"),
+            "MemTotal:       16000000 kB\nMemFree:          400000 kB\nMemAvailable:    2400000 kB\nBuffers:          100000 kB\nCached:          1500000 kB\n",
+        )
+        .unwrap();
+        assert_eq!(used_percent(&root), Some(85));
+
+        // Before `MemAvailable`, free memory plus the caches.
+        fs::write(
+            root.join("meminfo"),
+            "MemTotal:       16000000 kB\nMemFree:          400000 kB\nBuffers:          100000 kB\nCached:          1100000 kB\n",
+        )
+        .unwrap();
+        assert_eq!(used_percent(&root), Some(90));
+        // Without the caches either, just what's free.
+        fs::write(
+            root.join("meminfo"),
+            "MemTotal:       16000000 kB\nMemFree:         4000000 kB\n",
+        )
+        .unwrap();
+        assert_eq!(used_percent(&root), Some(75));
+        fs::write(root.join("meminfo"), "MemTotal:       16000000 kB\n").unwrap();
+        assert_eq!(used_percent(&root), None);
+
+        // All of it available, none of it, and the kernel overestimating.
+        for (available, used) in [(16000000, 0), (0, 100), (16000001, 0)] {
+            fs::write(
+                root.join("meminfo"),
+                format!(
+                    "MemTotal:       16000000 kB\nMemAvailable: {} kB\n",
+                    available
+                ),
+            )
+            .unwrap();
+            assert_eq!(used_percent(&root), Some(used));
+        }
+
+        // Nothing to take a share of.
+        fs::write(
+            root.join("meminfo"),
+            "MemTotal:              0 kB\nMemFree:               0 kB\n",
+        )
+        .unwrap();
+        assert_eq!(used_percent(&root), None);
+        fs::write(root.join("meminfo"), "MemAvailable:    2400000 kB\n").unwrap();
+        assert_eq!(used_percent(&root), None);
+    }
+}
@@ -0,0 +1,266 @@
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::output_with_timeout;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows whether the shell is inside a Nix shell (`nix-shell` or
+/// `nix develop`), and whether direnv has loaded the nearest `.envrc`.
+pub struct Nix<S> {
+    show_direnv: bool,
+    scheme: PhantomData<S>,
+}
+
+pub trait NixScheme: DefaultColors {
+    fn nix_fg() -> Color {
+        Self::default_fg()
+    }
+    fn nix_bg() -> Color {
+        Self::default_bg()
+    }
+    fn nix_pure_fg() -> Color {
+        Self::nix_fg()
+    }
+    fn nix_pure_bg() -> Color {
+        Self::nix_bg()
+    }
+    fn direnv_loaded_fg() -> Color {
+        Self::default_fg()
+    }
+    fn direnv_loaded_bg() -> Color {
+        Self::default_bg()
+    }
+    fn direnv_blocked_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn direnv_blocked_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: NixScheme> Nix<S> {
+    pub fn new(show_direnv: bool) -> Nix<S> {
+        Nix {
+            show_direnv,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const NIX_ICON: &str = "\u{f313}"; // nf-linux-nixos
+const DIRENV_ICON: &str = "\u{f07c}"; // nf-fa-folder_open
+const BLOCKED_ICON: &str = "\u{f023}"; // nf-fa-lock
+
+/// How long `direnv status` gets to say whether an `.envrc` is allowed.
+const DIRENV_TIMEOUT: Duration = Duration::from_millis(200);
+
+impl<S: NixScheme> Module for Nix<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let env = |key: &str| std::env::var(key).ok();
+
+        if let Some(shell) = nix_shell(&env) {
+            let mut label = NIX_ICON.to_string();
+            if let Some(name) = &shell.name {
+                label += &format!(" {}", name);
+            }
+            let style = if shell.pure {
+                label += " (pure)";
+                Style::simple(S::nix_pure_fg(), S::nix_pure_bg())
+            } else {
+                Style::simple(S::nix_fg(), S::nix_bg())
+            };
+            powerline.add_segment(label, style);
+        }
+
+        if !self.show_direnv {
+            return;
+        }
+        let Ok(cwd) = std::env::current_dir() else {
+            return;
+        };
+        let allowed = |envrc: &Path| {
+            let dir = envrc.parent()?;
+            let status = output_with_timeout(
+                Command::new("direnv").arg("status").current_dir(dir),
+                DIRENV_TIMEOUT,
+            )?;
+            rc_allowed(&status)
+        };
+        match direnv_state(&env, &cwd, &allowed) {
+            Some(Direnv::Loaded) => powerline.add_segment(
+                format!("{} direnv", DIRENV_ICON),
+                Style::simple(S::direnv_loaded_fg(), S::direnv_loaded_bg()),
+            ),
+            Some(Direnv::Blocked) => powerline.add_segment(
+                format!("{} direnv blocked", BLOCKED_ICON),
+                Style::simple(S::direnv_blocked_fg(), S::direnv_blocked_bg()),
+            ),
+            None => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct NixShell {
+    pure: bool,
+    /// The `name` of the derivation the shell was built from, unless it's
+    /// one of the generic defaults.
+    name: Option<String>,
+}
+
+/// Both `nix-shell` and `nix develop` set `IN_NIX_SHELL` to `pure` or
+/// `impure`, and export the shell derivation's `name`.
+fn nix_shell(env: &EnvGetter) -> Option<NixShell> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    let mode = var("IN_NIX_SHELL")?;
+    let name = var("name")
+        // The names `mkShell` and `nix-shell -p` give when nobody picked one
+        // say nothing the icon doesn't.
+        .filter(|name| !matches!(name.as_str(), "nix-shell" | "nix-shell-env" | "shell"));
+    Some(NixShell {
+        pure: mode == "pure",
+        name,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+enum Direnv {
+    Loaded,
+    /// There's an `.envrc` direnv hasn't loaded and won't until `direnv
+    /// allow`.
+    Blocked,
+}
+
+/// Compares the nearest `.envrc` with the one direnv says it loaded. One that
+/// isn't loaded only counts as blocked once `allowed` - `direnv status` outside
+/// tests - says so: it may just be that direnv isn't installed or hooked into
+/// the shell, or that the hook hasn't run yet. `None` when there's nothing to
+/// show.
+fn direnv_state(
+    env: &EnvGetter,
+    cwd: &Path,
+    allowed: &dyn Fn(&Path) -> Option<bool>,
+) -> Option<Direnv> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    let envrc = find_envrc(cwd);
+    // `DIRENV_DIR` is the loaded `.envrc`'s directory, prefixed with `-`.
+    let loaded = var("DIRENV_DIR").map(|dir| PathBuf::from(dir.trim_start_matches('-')));
+    match (envrc, loaded) {
+        (Some(envrc), Some(loaded)) if envrc.parent() == Some(&loaded) => Some(Direnv::Loaded),
+        (Some(envrc), _) => (allowed(&envrc) == Some(false)).then_some(Direnv::Blocked),
+        (None, Some(_)) => Some(Direnv::Loaded),
+        (None, None) => None,
+    }
+}
+
+/// Reads the `Found RC allowed` line of `direnv status`: `true`/`false` from
+/// older releases, `0` for allowed and `1` (not allowed) or `2` (denied) from
+/// newer ones.
+fn rc_allowed(status: &str) -> Option<bool> {
+    let value = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Found RC allowed "))?;
+    match value.trim() {
+        "true" | "0" => Some(true),
+        "false" | "1" | "2" => Some(false),
+        _ => None,
+    }
+}
+
+fn find_envrc(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join(".envrc"))
+        .find(|envrc| envrc.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn recognises_nix_shells() {
+        assert_eq!(nix_shell(&|_| None), None);
+
+        let env = |key: &str| match key {
+            "IN_NIX_SHELL" => Some("impure".to_string()),
+            "name" => Some("shop-dev".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            nix_shell(&env),
+            Some(NixShell {
+                pure: false,
+                name: Some("shop-dev".to_string()),
+            })
+        );
+
+        let env = |key: &str| match key {
+            "IN_NIX_SHELL" => Some("pure".to_string()),
+            "name" => Some("nix-shell".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            nix_shell(&env),
+            Some(NixShell {
+                pure: true,
+                name: None,
+            })
+        );
+    }
+
+    #[test]
+    fn tells_loaded_and_blocked_envrcs_apart() {
+        let project = TempDir::new("direnv");
+        let nested = project.join("src").join("app");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(project.join(".envrc"), "use flake\n").unwrap();
+
+        let blocked = |_: &Path| Some(false);
+        let allowed = |_: &Path| Some(true);
+        let no_direnv = |_: &Path| None;
+        assert_eq!(
+            direnv_state(&|_| None, &nested, &blocked),
+            Some(Direnv::Blocked)
+        );
+        // Without direnv, or before its hook has caught up, an `.envrc` that
+        // isn't loaded says nothing.
+        assert_eq!(direnv_state(&|_| None, &nested, &no_direnv), None);
+        assert_eq!(direnv_state(&|_| None, &nested, &allowed), None);
+
+        let loaded = format!("-{}", project.display());
+        let env = |key: &str| (key == "DIRENV_DIR").then(|| loaded.clone());
+        assert_eq!(direnv_state(&env, &nested, &blocked), Some(Direnv::Loaded));
+
+        // A different `.envrc` is still waiting to be allowed.
+        let other = format!("-{}", nested.display());
+        let env = |key: &str| (key == "DIRENV_DIR").then(|| other.clone());
+        assert_eq!(direnv_state(&env, &nested, &blocked), Some(Direnv::Blocked));
+    }
+
+    #[test]
+    fn reads_whether_direnv_allows_the_rc() {
+        let status = |allowed: &str| {
+            format!(
+                "direnv exec path /usr/bin/direnv\nFound RC path /src/app/.envrc\nFound RC allowed {}\nFound RC allowPath /x\n",
+                allowed
+            )
+        };
+        assert_eq!(rc_allowed(&status("true")), Some(true));
+        assert_eq!(rc_allowed(&status("0")), Some(true));
+        assert_eq!(rc_allowed(&status("false")), Some(false));
+        assert_eq!(rc_allowed(&status("1")), Some(false));
+        assert_eq!(rc_allowed(&status("2")), Some(false));
+        assert_eq!(rc_allowed("No .envrc or .env loaded\n"), None);
+    }
+}
@@ -1,34 +1,59 @@
-use std::collections::hash_map::DefaultHasher;
-use std::env;
-use std::fs::{self, File};
-use std::hash::{Hash, Hasher};
-use std::io::Write as _;
+use std::ffi::OsStr;
+use std::fs;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::process::Command;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::cache;
 use crate::colors::Color;
+use crate::config::{ForgeKind, ForgeTemplate, NotifyStyle, PrProvider};
 use crate::themes::DefaultColors;
-use crate::{Powerline, Style};
+use crate::utils::glob_match;
+use crate::{Marker, Powerline, Style};
 
+use super::git::forge_kind;
 use super::Module;
 
-/// How long a cached lookup stays fresh. Prompts rendered within this window
-/// reuse the cache and never touch the network.
+mod github;
+mod gitlab;
+
+/// How long a cached lookup stays fresh by default. Prompts rendered within
+/// this window reuse the cache and never touch the network.
 const CACHE_TTL: Duration = Duration::from_secs(60);
-/// Debounce window for the background refresher, so several prompts rendered in
-/// quick succession don't each spawn their own `gh` process.
+/// Default debounce window for the background refresher, so several prompts
+/// rendered in quick succession don't each spawn their own `gh` process.
 const REFRESH_DEBOUNCE: Duration = Duration::from_secs(20);
+/// The longest a failing lookup backs off for, unless the TTL is longer still.
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
 
-/// Branches that never have a PR of their own - skip all work for these.
+/// Branches that by default never have a PR of their own - only their CI
+/// status is looked up, and only when the `ci` option is on.
 const SKIP_BRANCHES: &[&str] = &["develop", "main", "master", "HEAD"];
 
 pub struct Pr<S> {
     /// Whether to append the CI check-status dot after the PR number.
     show_status: bool,
+    /// Whether to append the review, unresolved-thread and conflict glyphs.
+    show_review: bool,
+    /// Whether to break the CI status down into per-state counts.
+    check_counts: bool,
+    /// Whether to show the CI status of `HEAD` when the branch has no PR.
+    ci: bool,
+    provider: PrProvider,
+    /// GitHub GraphQL endpoint override, for GitHub Enterprise.
+    api_url: Option<String>,
+    /// Self-hosted forges, whose `kind` picks the provider for their hosts.
+    forges: Vec<ForgeTemplate>,
+    cache_ttl: Duration,
+    refresh_debounce: Duration,
+    /// Glob patterns for the branches that aren't looked up.
+    skip_branches: Vec<String>,
+    /// How to announce that the PR's checks finished, if at all.
+    notify: Option<NotifyStyle>,
+    bell: bool,
     scheme: PhantomData<S>,
 }
 
@@ -73,21 +98,134 @@ pub trait PrScheme: DefaultColors {
     fn pr_status_icon() -> &'static str {
         "\u{25cf}" // ● black circle
     }
+    fn pr_check_success_icon() -> &'static str {
+        "\u{2713}" // ✓ check mark
+    }
+    fn pr_check_failure_icon() -> &'static str {
+        "\u{2717}" // ✗ ballot x
+    }
+
+    fn pr_approved_fg() -> Color {
+        Self::pr_status_success_fg()
+    }
+    fn pr_changes_requested_fg() -> Color {
+        Self::pr_status_failure_fg()
+    }
+    fn pr_review_required_fg() -> Color {
+        Self::pr_status_pending_fg()
+    }
+    fn pr_threads_fg() -> Color {
+        Self::default_fg()
+    }
+    fn pr_conflict_fg() -> Color {
+        Self::pr_status_failure_fg()
+    }
+    fn pr_ci_fg() -> Color {
+        Self::default_fg()
+    }
+    fn pr_ci_bg() -> Color {
+        Self::default_bg()
+    }
+    fn pr_ci_icon() -> &'static str {
+        "\u{f085}" // nf-fa-gears
+    }
+    fn pr_approved_icon() -> &'static str {
+        "\u{f00c}" // nf-fa-check
+    }
+    fn pr_changes_requested_icon() -> &'static str {
+        "\u{f040}" // nf-fa-pencil
+    }
+    fn pr_review_required_icon() -> &'static str {
+        "\u{f06e}" // nf-fa-eye
+    }
+    fn pr_threads_icon() -> &'static str {
+        "\u{f075}" // nf-fa-comment
+    }
+    fn pr_conflict_icon() -> &'static str {
+        "\u{f071}" // nf-fa-warning
+    }
+    fn pr_stale_fg() -> Color {
+        Self::default_fg()
+    }
+    fn pr_stale_icon() -> &'static str {
+        "\u{f1da}" // nf-fa-history
+    }
 }
 
 impl<S: PrScheme> Default for Pr<S> {
     fn default() -> Self {
-        Self::new(true)
+        Self::new(true, true, PrProvider::Auto, None)
     }
 }
 
 impl<S: PrScheme> Pr<S> {
-    pub fn new(show_status: bool) -> Pr<S> {
+    pub fn new(
+        show_status: bool,
+        show_review: bool,
+        provider: PrProvider,
+        api_url: Option<String>,
+    ) -> Pr<S> {
         Pr {
             show_status,
+            show_review,
+            check_counts: false,
+            ci: false,
+            provider,
+            api_url,
+            forges: Vec::new(),
+            cache_ttl: CACHE_TTL,
+            refresh_debounce: REFRESH_DEBOUNCE,
+            skip_branches: SKIP_BRANCHES.iter().map(|b| b.to_string()).collect(),
+            notify: None,
+            bell: false,
             scheme: PhantomData,
         }
     }
+
+    /// Sets how long a lookup stays fresh, and how long to wait before
+    /// retrying a refresh that hasn't finished.
+    pub fn cache(mut self, ttl: Duration, refresh_debounce: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self.refresh_debounce = refresh_debounce;
+        self
+    }
+
+    /// Replaces the branches that are never looked up. Patterns may use `*`
+    /// and `?`, as in `release/*`.
+    pub fn skip_branches(mut self, skip_branches: Vec<String>) -> Self {
+        self.skip_branches = skip_branches;
+        self
+    }
+
+    /// Sends a terminal notification and/or rings the bell on the first
+    /// prompt after the PR's checks go from pending to passed or failed.
+    pub fn notify(mut self, notify: Option<NotifyStyle>, bell: bool) -> Self {
+        self.notify = notify;
+        self.bell = bell;
+        self
+    }
+
+    /// Shows the number of passing, failing and pending checks (`✓12 ✗1 ●3`)
+    /// in place of the single status dot.
+    pub fn check_counts(mut self, check_counts: bool) -> Self {
+        self.check_counts = check_counts;
+        self
+    }
+
+    /// Recognises the provider of self-hosted forges by the `kind` they're
+    /// configured with, as the `git` segment's `forges` option does.
+    pub fn forges(mut self, forges: Vec<ForgeTemplate>) -> Self {
+        self.forges = forges;
+        self
+    }
+
+    /// Shows the CI status of the `HEAD` commit in a segment of its own when
+    /// the branch has no PR, including on the branches that are never looked
+    /// up (`main` and the like).
+    pub fn ci(mut self, ci: bool) -> Self {
+        self.ci = ci;
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -100,6 +238,14 @@ enum PrState {
 }
 
 impl PrState {
+    fn name(self) -> &'static str {
+        match self {
+            PrState::Draft => "draft",
+            PrState::Open => "open",
+            PrState::Merged => "merged",
+            PrState::Closed => "closed",
+        }
+    }
     /// Whether the PR is still in progress (open or draft, but not merged or
     /// closed). Only these PRs show the CI status indicator.
     fn is_open(self) -> bool {
@@ -118,7 +264,7 @@ impl PrState {
 }
 
 /// Aggregate state of the PR's checks, collapsed from the individual check runs
-/// and status contexts reported by GitHub.
+/// and status contexts reported by GitHub, or GitLab's pipeline status.
 #[derive(Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 enum CheckStatus {
@@ -127,6 +273,31 @@ enum CheckStatus {
     Pending,
 }
 
+/// How many of the PR's checks are in each state.
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+struct CheckCounts {
+    success: u32,
+    failure: u32,
+    pending: u32,
+}
+
+impl CheckCounts {
+    /// Collapses the counts into a single status. Failure beats pending, which
+    /// beats success. Returns `None` when there are no meaningful checks, so the
+    /// dot renders nothing rather than misleading the reader.
+    fn status(self) -> Option<CheckStatus> {
+        if self.failure > 0 {
+            Some(CheckStatus::Failure)
+        } else if self.pending > 0 {
+            Some(CheckStatus::Pending)
+        } else if self.success > 0 {
+            Some(CheckStatus::Success)
+        } else {
+            None
+        }
+    }
+}
+
 impl CheckStatus {
     /// Picks the dot's foreground colour from the active scheme. The dot shares
     /// the PR segment's background, so there's no background to choose here.
@@ -137,6 +308,39 @@ impl CheckStatus {
             CheckStatus::Pending => S::pr_status_pending_fg(),
         }
     }
+
+    /// `glyph` in this status' colour, linked to `failed_url` if it's a failure.
+    fn marker<'a, S: PrScheme>(self, glyph: &'a str, failed_url: Option<&'a str>) -> Marker<'a> {
+        let marker = Marker::new(glyph, self.fg::<S>());
+        match self {
+            CheckStatus::Failure => marker.link(failed_url),
+            _ => marker,
+        }
+    }
+}
+
+/// GitHub's `reviewDecision`. GitLab only reports the two negative outcomes.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl ReviewDecision {
+    fn marker<S: PrScheme>(self) -> Marker<'static> {
+        let (glyph, color) = match self {
+            ReviewDecision::Approved => (S::pr_approved_icon(), S::pr_approved_fg()),
+            ReviewDecision::ChangesRequested => {
+                (S::pr_changes_requested_icon(), S::pr_changes_requested_fg())
+            }
+            ReviewDecision::ReviewRequired => {
+                (S::pr_review_required_icon(), S::pr_review_required_fg())
+            }
+        };
+        Marker::new(glyph, color)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -149,66 +353,255 @@ struct PrInfo {
     /// compatibility with caches written before this field existed.
     #[serde(default)]
     checks: Option<CheckStatus>,
+    /// The per-state counts behind `checks`, and where to read about the first
+    /// failing check. Defaulted for the same reason.
+    #[serde(default)]
+    check_counts: CheckCounts,
+    #[serde(default)]
+    failed_check_url: Option<String>,
+    /// Where the PR lives. Never `Auto`; caches from before GitLab support
+    /// default to GitHub.
+    #[serde(default = "github")]
+    provider: PrProvider,
+    /// The review fields below are defaulted like `checks`, for caches written
+    /// before they existed.
+    #[serde(default)]
+    review: Option<ReviewDecision>,
+    #[serde(default)]
+    unresolved_threads: u32,
+    #[serde(default)]
+    conflicts: bool,
+}
+
+fn github() -> PrProvider {
+    PrProvider::GitHub
+}
+
+/// CI results for a commit not covered by a PR, tallied like a PR's checks.
+#[derive(Serialize, Deserialize)]
+struct CommitChecks {
+    commit: String,
+    counts: CheckCounts,
+    failed_check_url: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct PrCache {
     branch: String,
+    /// The repository's root, for `superline cache list`. Missing from caches
+    /// written before it was recorded.
+    #[serde(default)]
+    repo: Option<PathBuf>,
     /// `None` means "looked up, but no PR exists for this branch" - cached so we
     /// don't re-query on every prompt.
     pr: Option<PrInfo>,
+    /// The checks on `HEAD`, looked up only when there's no PR and the `ci`
+    /// option is on. Entries for an older commit are refreshed straight away.
+    #[serde(default)]
+    commit_checks: Option<CommitChecks>,
+    /// How many refreshes in a row have failed. The lookup above is then left
+    /// over from the last one that worked, and retries back off.
+    #[serde(default)]
+    failures: u32,
+    /// Set when the PR's checks finish, until a prompt has announced it.
+    #[serde(default)]
+    finished_checks: Option<FinishedChecks>,
     fetched_at: u64,
 }
 
+/// The status the PR's checks finished with, and when a refresh noticed. The
+/// time tells this finish apart from earlier ones in the `.announced` marker
+/// a prompt leaves once it has announced it.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct FinishedChecks {
+    status: CheckStatus,
+    at: u64,
+}
+
+/// A lookup that couldn't be answered - no network, expired credentials, rate
+/// limiting - as opposed to one that found nothing.
+struct FetchFailed;
+
+type Fetched<T> = Result<Option<T>, FetchFailed>;
+
+/// How long to wait before refreshing again: the TTL, doubled for each failed
+/// refresh in a row, up to [`MAX_BACKOFF`].
+fn retry_after(ttl: Duration, failures: u32) -> Duration {
+    ttl.saturating_mul(1 << failures.min(16))
+        .min(MAX_BACKOFF.max(ttl))
+}
+
 impl<S: PrScheme> Module for Pr<S> {
     fn append_segments(&mut self, powerline: &mut Powerline) {
-        let Some((branch, repo_root)) = current_branch_and_root() else {
+        let Some((branch, repo_root, head)) = current_branch_and_root() else {
             return;
         };
-        if SKIP_BRANCHES.contains(&branch.as_str()) {
+        let skipped = self.is_skipped(&branch);
+        if skipped && !self.ci {
             return;
         }
 
         // Without a cache directory we'd have to fetch synchronously, which
         // could block the prompt on a network request - so bail instead.
-        let Some(cache_path) = cache_path_for(&repo_root, &branch) else {
+        let Some(cache_path) = cache::cache_path("pr", (&repo_root, &branch)) else {
             return;
         };
 
-        let cache = read_cache(&cache_path).filter(|c| c.branch == branch);
+        let cache = cache::read::<PrCache>(&cache_path).filter(|c| c.branch == branch);
 
         // Refresh in the background when the cache is missing or stale. This
         // never blocks rendering - the result is picked up by a later prompt.
-        if cache.as_ref().is_none_or(|c| is_stale(c.fetched_at)) {
-            spawn_refresh(&branch, &repo_root, &cache_path);
+        // A new commit without a PR needs its checks looked up at once, unless
+        // lookups are failing.
+        let head_moved = |c: &PrCache| {
+            self.ci
+                && c.failures == 0
+                && c.pr.is_none()
+                && c.commit_checks
+                    .as_ref()
+                    .is_none_or(|checks| checks.commit != head)
+        };
+        if cache.as_ref().is_none_or(|c| {
+            cache::is_stale(c.fetched_at, retry_after(self.cache_ttl, c.failures)) || head_moved(c)
+        }) {
+            self.spawn_refresh(&branch, &repo_root, &cache_path, &head, skipped);
         }
 
         // Render whatever we have right now (possibly slightly stale).
-        if let Some(PrCache { pr: Some(pr), .. }) = cache {
-            let label = format!("{} #{}", S::pr_icon(), pr.number);
+        let Some(cache) = cache else {
+            return;
+        };
+        if let Some(status) = take_finished_checks(&cache, &cache_path) {
+            self.announce(powerline, cache.pr.as_ref(), status);
+        }
+        // Left over from before the last refresh failed, so may be out of date.
+        let stale = (cache.failures > 0).then(|| Marker::new(S::pr_stale_icon(), S::pr_stale_fg()));
+        if let Some(pr) = cache.pr {
+            // GitLab refers to merge requests as `!123`.
+            let sigil = match pr.provider {
+                PrProvider::GitLab => '!',
+                _ => '#',
+            };
+            let label = format!("{} {}{}", S::pr_icon(), sigil, pr.number);
             let (fg, bg) = pr.state.style::<S>();
 
-            // The CI status, when enabled and meaningful, renders as a coloured
-            // dot tucked into the same segment right after the PR number. It's
-            // only shown while a PR is still in progress - the checks are stale
-            // or irrelevant once a PR is merged or closed.
-            let marker = (self.show_status && pr.state.is_open())
-                .then(|| {
-                    pr.checks
-                        .map(|status| (S::pr_status_icon(), status.fg::<S>()))
+            // The CI status and review state, when enabled and meaningful,
+            // render as coloured glyphs tucked into the same segment right after
+            // the PR number. They're only shown while a PR is still in progress -
+            // they're stale or irrelevant once a PR is merged or closed.
+            let checks = if pr.state.is_open() && self.show_status {
+                self.check_glyphs(pr.check_counts, pr.checks)
+            } else {
+                Vec::new()
+            };
+            let threads = format!("{}{}", S::pr_threads_icon(), pr.unresolved_threads);
+            // The failing check's details page, reachable by clicking its glyph.
+            let failed_url = pr.failed_check_url.as_deref();
+
+            let mut markers: Vec<Marker> = checks
+                .iter()
+                .map(|(glyph, status)| status.marker::<S>(glyph, failed_url))
+                .collect();
+            if pr.state.is_open() && self.show_review {
+                markers.extend(pr.review.map(ReviewDecision::marker::<S>));
+                if pr.unresolved_threads > 0 {
+                    markers.push(Marker::new(&threads, S::pr_threads_fg()));
+                }
+                if pr.conflicts {
+                    markers.push(Marker::new(S::pr_conflict_icon(), S::pr_conflict_fg()));
+                }
+            }
+            markers.extend(stale);
+
+            powerline.add_hyperlink_segment(&label, &pr.url, Style::simple(fg, bg), &markers);
+        } else if let Some(checks) = cache.commit_checks.filter(|c| self.ci && c.commit == head) {
+            let glyphs = self.check_glyphs(checks.counts, checks.counts.status());
+            if glyphs.is_empty() {
+                return;
+            }
+            let markers: Vec<Marker> = glyphs
+                .iter()
+                .map(|(glyph, status)| {
+                    status.marker::<S>(glyph, checks.failed_check_url.as_deref())
                 })
-                .flatten();
+                .chain(stale)
+                .collect();
+            let style = Style::simple(S::pr_ci_fg(), S::pr_ci_bg());
+            powerline.add_marked_segment(S::pr_ci_icon(), style, &markers);
+        }
+    }
+}
 
-            powerline.add_hyperlink_segment(&label, &pr.url, Style::simple(fg, bg), marker);
+impl<S: PrScheme> Pr<S> {
+    fn announce(&self, powerline: &mut Powerline, pr: Option<&PrInfo>, status: CheckStatus) {
+        if let (Some(style), Some(pr)) = (self.notify, pr) {
+            let title = match pr.provider {
+                PrProvider::GitLab => format!("MR !{}", pr.number),
+                _ => format!("PR #{}", pr.number),
+            };
+            let body = match status {
+                CheckStatus::Failure => "Checks failed",
+                _ => "Checks passed",
+            };
+            powerline.notify(style, &title, body);
+        }
+        if self.bell {
+            powerline.bell();
+        }
+    }
+
+    fn is_skipped(&self, branch: &str) -> bool {
+        self.skip_branches
+            .iter()
+            .any(|pattern| glob_match(pattern, branch))
+    }
+
+    /// The CI glyphs: per-state counts when enabled, otherwise a single dot for
+    /// `status`. Caches from before the counts existed only have the status.
+    fn check_glyphs(
+        &self,
+        counts: CheckCounts,
+        status: Option<CheckStatus>,
+    ) -> Vec<(String, CheckStatus)> {
+        if self.check_counts && counts.status().is_some() {
+            [
+                (
+                    counts.success,
+                    S::pr_check_success_icon(),
+                    CheckStatus::Success,
+                ),
+                (
+                    counts.failure,
+                    S::pr_check_failure_icon(),
+                    CheckStatus::Failure,
+                ),
+                (counts.pending, S::pr_status_icon(), CheckStatus::Pending),
+            ]
+            .into_iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, icon, status)| (format!("{}{}", icon, count), status))
+            .collect()
+        } else {
+            status
+                .map(|status| (S::pr_status_icon().to_string(), status))
+                .into_iter()
+                .collect()
         }
     }
 }
 
-/// Resolves the current branch name and repository root in a single, fast,
-/// network-free git invocation. Returns `None` outside a git repository.
-fn current_branch_and_root() -> Option<(String, PathBuf)> {
+/// Resolves the current branch name, repository root and `HEAD` commit in a
+/// single, fast, network-free git invocation. Returns `None` outside a git
+/// repository, or before the first commit.
+fn current_branch_and_root() -> Option<(String, PathBuf, String)> {
     let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD", "--show-toplevel"])
+        .args([
+            "rev-parse",
+            "HEAD",
+            "--abbrev-ref",
+            "HEAD",
+            "--show-toplevel",
+        ])
         .output()
         .ok()?;
 
@@ -218,163 +611,325 @@ fn current_branch_and_root() -> Option<(String, PathBuf)> {
 
     let text = String::from_utf8(output.stdout).ok()?;
     let mut lines = text.lines();
+    let head = lines.next()?.trim().to_string();
     let branch = lines.next()?.trim().to_string();
     let root = lines.next()?.trim();
 
-    if branch.is_empty() || root.is_empty() {
+    if head.is_empty() || branch.is_empty() || root.is_empty() {
         return None;
     }
 
-    Some((branch, PathBuf::from(root)))
+    Some((branch, PathBuf::from(root), head))
 }
 
-fn cache_path_for(repo_root: &Path, branch: &str) -> Option<PathBuf> {
-    let base = crate::platform::cache_dir()?;
+impl<S: PrScheme> Pr<S> {
+    fn spawn_refresh(
+        &self,
+        branch: &str,
+        repo_root: &Path,
+        cache_path: &Path,
+        head: &str,
+        skipped: bool,
+    ) {
+        let provider = match self.provider {
+            PrProvider::Auto => "auto",
+            PrProvider::GitHub => "github",
+            PrProvider::GitLab => "gitlab",
+        };
+        let forges: Vec<String> = self
+            .forges
+            .iter()
+            .filter_map(|forge| Some(format!("{}={}", forge.host, forge.kind?.name())))
+            .collect();
 
-    let mut hasher = DefaultHasher::new();
-    repo_root.hash(&mut hasher);
-    branch.hash(&mut hasher);
+        let mut args: Vec<&OsStr> = vec![
+            "refresh-pr".as_ref(),
+            "--branch".as_ref(),
+            branch.as_ref(),
+            "--repo-dir".as_ref(),
+            repo_root.as_os_str(),
+            "--cache".as_ref(),
+            cache_path.as_os_str(),
+            "--provider".as_ref(),
+            provider.as_ref(),
+        ];
+        if let Some(api_url) = &self.api_url {
+            args.push("--api-url".as_ref());
+            args.push(api_url.as_ref());
+        }
+        for forge in &forges {
+            args.push("--forge".as_ref());
+            args.push(forge.as_ref());
+        }
+        if self.ci {
+            args.push("--commit".as_ref());
+            args.push(head.as_ref());
+        }
+        if skipped {
+            args.push("--skip-pr".as_ref());
+        }
 
-    Some(
-        base.join("superline")
-            .join(format!("pr-{:016x}.json", hasher.finish())),
-    )
+        cache::spawn_refresh(cache_path, self.refresh_debounce, args);
+    }
 }
 
-fn read_cache(path: &Path) -> Option<PrCache> {
-    serde_json::from_reader(File::open(path).ok()?).ok()
+/// Where a branch's PR is looked up: the `pr` segment's `provider` and
+/// `api_url`, plus the self-hosted forges whose `kind` decides the provider
+/// when it's `Auto`.
+pub struct PrLookup<'a> {
+    pub provider: PrProvider,
+    pub api_url: Option<&'a str>,
+    pub forges: &'a [ForgeTemplate],
 }
 
-fn now_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0)
+/// Performs the blocking `gh`/`glab` lookup and writes the cache. Invoked by the
+/// hidden `refresh-pr` subcommand from the detached process spawned above.
+/// Always fetches the check status too - rendering it is a display-time choice,
+/// so the cache stays the same regardless of config. With a `commit`, that
+/// commit's checks are looked up too when there's no PR. With `skip_pr`, only
+/// the commit is.
+///
+/// A failed lookup keeps the previous results and counts the failure, so the
+/// next refresh backs off.
+pub fn refresh_pr(
+    branch: &str,
+    repo_dir: &Path,
+    cache_path: &Path,
+    lookup: &PrLookup,
+    commit: Option<&str>,
+    skip_pr: bool,
+) {
+    let remote_url = remote_url(repo_dir);
+    let remote_url = remote_url.as_deref();
+    let provider = match lookup.provider {
+        PrProvider::Auto => detect_provider(remote_url, lookup.forges),
+        provider => provider,
+    };
+    let fetch = || -> Result<_, FetchFailed> {
+        let pr = match provider {
+            _ if skip_pr => None,
+            PrProvider::GitLab => gitlab::fetch_mr(branch, repo_dir)?,
+            _ => github::fetch_pr(branch, repo_dir, remote_url, lookup)?,
+        };
+        let commit_checks = match (commit, &pr) {
+            (Some(commit), None) => match provider {
+                PrProvider::GitLab => gitlab::fetch_commit_checks(commit, repo_dir)?,
+                _ => github::fetch_commit_checks(commit, repo_dir, remote_url, lookup)?,
+            },
+            _ => None,
+        };
+        Ok((pr, commit_checks))
+    };
+
+    let previous = cache::read::<PrCache>(cache_path).filter(|c| c.branch == branch);
+    let cache = match (fetch(), previous) {
+        (Ok((pr, commit_checks)), previous) => PrCache {
+            branch: branch.to_string(),
+            repo: Some(repo_dir.to_path_buf()),
+            finished_checks: finished_checks(
+                previous.as_ref(),
+                pr.as_ref(),
+                announced_at(cache_path),
+            ),
+            pr,
+            commit_checks,
+            failures: 0,
+            fetched_at: cache::now_secs(),
+        },
+        (Err(FetchFailed), Some(previous)) => PrCache {
+            repo: Some(repo_dir.to_path_buf()),
+            failures: previous.failures + 1,
+            fetched_at: cache::now_secs(),
+            ..previous
+        },
+        (Err(FetchFailed), None) => PrCache {
+            branch: branch.to_string(),
+            repo: Some(repo_dir.to_path_buf()),
+            pr: None,
+            commit_checks: None,
+            failures: 1,
+            finished_checks: None,
+            fetched_at: cache::now_secs(),
+        },
+    };
+
+    cache::write(cache_path, &cache);
+    cache::finish_refresh(cache_path);
 }
 
-fn is_stale(fetched_at: u64) -> bool {
-    now_secs().saturating_sub(fetched_at) >= CACHE_TTL.as_secs()
+/// Looks up the current branch's PR and waits for the answer, for `superline
+/// cache refresh`. The options are the `pr` segment's; HEAD's CI is only looked
+/// up with `ci`. Returns the refreshed entry, or `None` outside a git
+/// repository.
+pub fn refresh_current_pr(
+    lookup: &PrLookup,
+    skip_branches: &[String],
+    ci: bool,
+) -> Option<CachedPr> {
+    let (branch, repo_root, head) = current_branch_and_root()?;
+    let cache_path = cache::cache_path("pr", (&repo_root, &branch))?;
+    let skip_pr = skip_branches
+        .iter()
+        .any(|pattern| glob_match(pattern, &branch));
+
+    refresh_pr(
+        &branch,
+        &repo_root,
+        &cache_path,
+        lookup,
+        ci.then_some(head.as_str()),
+        skip_pr,
+    );
+    CachedPr::read(cache_path)
 }
 
-/// True if a refresh was kicked off recently enough that we should let it
-/// finish rather than spawning another one.
-fn refresh_in_flight(lock_path: &Path) -> bool {
-    fs::metadata(lock_path)
-        .and_then(|meta| meta.modified())
-        .ok()
-        .and_then(|modified| modified.elapsed().ok())
-        .is_some_and(|elapsed| elapsed < REFRESH_DEBOUNCE)
+/// A cached lookup, as `superline cache list` shows it.
+pub struct CachedPr {
+    pub path: PathBuf,
+    pub repo: Option<PathBuf>,
+    pub branch: String,
+    /// Seconds since the lookup, or the last failed attempt.
+    pub age: u64,
+    /// What was found, e.g. `#12 open, checks failing`.
+    pub state: String,
 }
 
-/// Spawns a detached process to refresh the cache. The child's stdio is
-/// redirected to null so the shell's command substitution doesn't block
-/// waiting on the inherited pipe.
-fn spawn_refresh(branch: &str, repo_root: &Path, cache_path: &Path) {
-    let lock_path = cache_path.with_extension("lock");
-    if refresh_in_flight(&lock_path) {
-        return;
+impl CachedPr {
+    /// Every cached lookup, most recent first.
+    pub fn all() -> Vec<CachedPr> {
+        let mut all: Vec<CachedPr> = cache::entries("pr")
+            .into_iter()
+            .filter_map(CachedPr::read)
+            .collect();
+        all.sort_by_key(|cached| cached.age);
+        all
     }
 
-    if let Some(parent) = cache_path.parent() {
-        let _ = fs::create_dir_all(parent);
+    fn read(path: PathBuf) -> Option<CachedPr> {
+        let cache = cache::read::<PrCache>(&path)?;
+        Some(CachedPr {
+            repo: cache.repo.clone(),
+            branch: cache.branch.clone(),
+            age: cache::now_secs().saturating_sub(cache.fetched_at),
+            state: describe(&cache),
+            path,
+        })
     }
-    // Touch the lock up front to debounce concurrent prompts.
-    let _ = File::create(&lock_path);
+}
 
-    let Ok(exe) = env::current_exe() else {
-        return;
+fn describe(cache: &PrCache) -> String {
+    let checks = |status: Option<CheckStatus>| match status {
+        Some(CheckStatus::Success) => ", checks passing",
+        Some(CheckStatus::Failure) => ", checks failing",
+        Some(CheckStatus::Pending) => ", checks pending",
+        None => "",
     };
 
-    let _ = Command::new(exe)
-        .arg("refresh-pr")
-        .args(["--branch", branch])
-        .arg("--repo-dir")
-        .arg(repo_root)
-        .arg("--cache")
-        .arg(cache_path)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn();
+    let mut state = match (&cache.pr, &cache.commit_checks) {
+        (Some(pr), _) => {
+            let sigil = match pr.provider {
+                PrProvider::GitLab => '!',
+                _ => '#',
+            };
+            let state = pr.state.name();
+            format!("{}{} {}{}", sigil, pr.number, state, checks(pr.checks))
+        }
+        (None, Some(commit)) => format!("no PR{}", checks(commit.counts.status())),
+        (None, None) => "no PR".to_string(),
+    };
+    if cache.failures > 0 {
+        state += &format!(" (last {} refreshes failed)", cache.failures);
+    }
+    state
 }
 
-/// Performs the blocking `gh` lookup and writes the cache. Invoked by the
-/// hidden `refresh-pr` subcommand from the detached process spawned above.
-/// Always fetches the check status too - rendering it is a display-time choice,
-/// so the cache stays the same regardless of config.
-pub fn refresh_pr(branch: &str, repo_dir: &Path, cache_path: &Path) {
-    let cache = PrCache {
-        branch: branch.to_string(),
-        pr: fetch_pr(branch, repo_dir),
-        fetched_at: now_secs(),
-    };
+/// The status `pr`'s checks just finished with, if they were pending at the
+/// `previous` lookup. A finish no prompt has announced yet (one `announced_at`
+/// a different time) is carried over, unless the checks have started running
+/// again.
+fn finished_checks(
+    previous: Option<&PrCache>,
+    pr: Option<&PrInfo>,
+    announced_at: Option<u64>,
+) -> Option<FinishedChecks> {
+    let (previous, pr) = (previous?, pr?);
+    let was = previous
+        .pr
+        .as_ref()
+        .filter(|p| p.number == pr.number && p.provider == pr.provider)?;
+    match (was.checks, pr.checks) {
+        (
+            Some(CheckStatus::Pending),
+            Some(status @ (CheckStatus::Success | CheckStatus::Failure)),
+        ) => Some(FinishedChecks {
+            status,
+            at: cache::now_secs(),
+        }),
+        (_, Some(CheckStatus::Pending) | None) => None,
+        _ => previous
+            .finished_checks
+            .filter(|finished| Some(finished.at) != announced_at),
+    }
+}
+
+/// The marker recording which finish a prompt announced last. The entry
+/// itself is left to the refresh process, which may be rewriting it.
+fn announced_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("announced")
+}
+
+fn announced_at(cache_path: &Path) -> Option<u64> {
+    fs::read_to_string(announced_path(cache_path))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
 
-    write_cache(cache_path, &cache);
-    let _ = fs::remove_file(cache_path.with_extension("lock"));
+/// The status of finished checks no prompt has announced yet, marking them
+/// announced so the next prompt stays quiet.
+fn take_finished_checks(cache: &PrCache, cache_path: &Path) -> Option<CheckStatus> {
+    let finished = cache
+        .finished_checks
+        .filter(|finished| announced_at(cache_path) != Some(finished.at))?;
+    fs::write(announced_path(cache_path), finished.at.to_string()).ok()?;
+    Some(finished.status)
 }
 
-fn fetch_pr(branch: &str, repo_dir: &Path) -> Option<PrInfo> {
-    let output = Command::new("gh")
+/// The URL of the default remote, as `git` resolves it (`insteadOf` included).
+fn remote_url(repo_dir: &Path) -> Option<String> {
+    let output = Command::new("git")
         .current_dir(repo_dir)
-        .args([
-            "pr",
-            "view",
-            branch,
-            "--json",
-            "number,url,state,isDraft,statusCheckRollup",
-        ])
+        .args(["ls-remote", "--get-url"])
         .output()
         .ok()?;
+    let url = String::from_utf8(output.stdout).ok()?;
+    Some(url.trim().to_string()).filter(|url| !url.is_empty())
+}
 
-    // A non-zero exit usually just means there's no PR for this branch.
-    if !output.status.success() {
-        return None;
+/// GitLab when the default remote is a GitLab host, recognised or configured
+/// in `forges`, otherwise GitHub, which was the only provider before and stays
+/// the fallback.
+fn detect_provider(remote_url: Option<&str>, forges: &[ForgeTemplate]) -> PrProvider {
+    match remote_url.and_then(|url| forge_kind(url, forges)) {
+        Some(ForgeKind::GitLab) => PrProvider::GitLab,
+        _ => PrProvider::GitHub,
     }
-
-    let gh: GhPr = serde_json::from_slice(&output.stdout).ok()?;
-
-    // A draft PR is reported as OPEN with `isDraft: true`, so check that first.
-    let state = if gh.is_draft {
-        PrState::Draft
-    } else {
-        match gh.state.as_str() {
-            "MERGED" => PrState::Merged,
-            "CLOSED" => PrState::Closed,
-            _ => PrState::Open,
-        }
-    };
-
-    Some(PrInfo {
-        number: gh.number,
-        url: gh.url,
-        state,
-        checks: aggregate(&gh.status_check_rollup),
-    })
 }
 
-/// Collapses individual checks into a single status. Failure beats pending,
-/// which beats success. Returns `None` when there are no meaningful checks, so
-/// the dot renders nothing rather than misleading the reader.
-fn aggregate(checks: &[CheckItem]) -> Option<CheckStatus> {
-    let mut any_pending = false;
-    let mut any_success = false;
-
+/// Counts the checks in each state. Neutral ones (skipped and the like)
+/// aren't counted, as they shouldn't tip the dot either way.
+fn tally(checks: impl IntoIterator<Item = CheckOutcome>) -> CheckCounts {
+    let mut counts = CheckCounts::default();
     for check in checks {
-        match check.outcome() {
-            CheckOutcome::Failure => return Some(CheckStatus::Failure),
-            CheckOutcome::Pending => any_pending = true,
-            CheckOutcome::Success => any_success = true,
+        match check {
+            CheckOutcome::Success => counts.success += 1,
+            CheckOutcome::Failure => counts.failure += 1,
+            CheckOutcome::Pending => counts.pending += 1,
             CheckOutcome::Neutral => {}
         }
     }
-
-    if any_pending {
-        Some(CheckStatus::Pending)
-    } else if any_success {
-        Some(CheckStatus::Success)
-    } else {
-        None
-    }
+    counts
 }
 
 enum CheckOutcome {
@@ -384,68 +939,133 @@ enum CheckOutcome {
     Neutral,
 }
 
-/// A single entry in GitHub's `statusCheckRollup`. Check runs report
-/// `status`/`conclusion`; legacy status contexts report `state`.
-#[derive(Deserialize)]
-struct CheckItem {
-    #[serde(default)]
-    status: Option<String>,
-    #[serde(default)]
-    conclusion: Option<String>,
-    #[serde(default)]
-    state: Option<String>,
-}
-
-impl CheckItem {
-    fn outcome(&self) -> CheckOutcome {
-        // Legacy commit-status contexts carry a `state` instead of a status/
-        // conclusion pair.
-        if let Some(state) = &self.state {
-            return match state.as_str() {
-                "SUCCESS" => CheckOutcome::Success,
-                "PENDING" | "EXPECTED" => CheckOutcome::Pending,
-                _ => CheckOutcome::Failure, // FAILURE, ERROR
-            };
-        }
+#[cfg(test)]
+mod tests {
+    use super::{
+        detect_provider, finished_checks, retry_after, take_finished_checks, CheckStatus,
+        FinishedChecks, PrCache, PrInfo, MAX_BACKOFF,
+    };
+    use crate::config::{ForgeKind, ForgeTemplate, PrProvider};
+    use crate::utils::TempDir;
+    use std::time::Duration;
 
-        match self.status.as_deref() {
-            Some("COMPLETED") => match self.conclusion.as_deref() {
-                Some("SUCCESS") => CheckOutcome::Success,
-                // Skipped / neutral checks shouldn't tip the dot either way.
-                Some("SKIPPED") | Some("NEUTRAL") => CheckOutcome::Neutral,
-                // FAILURE, TIMED_OUT, CANCELLED, ACTION_REQUIRED, STARTUP_FAILURE
-                _ => CheckOutcome::Failure,
-            },
-            // QUEUED, IN_PROGRESS, WAITING, PENDING, REQUESTED, ...
-            Some(_) => CheckOutcome::Pending,
-            None => CheckOutcome::Neutral,
+    fn cache(checks: Option<CheckStatus>) -> PrCache {
+        let pr = format!(
+            r#"{{"number": 7, "url": "u", "state": "open", "checks": {}}}"#,
+            serde_json::to_string(&checks).unwrap()
+        );
+        PrCache {
+            branch: "feature".to_string(),
+            repo: None,
+            pr: serde_json::from_str(&pr).unwrap(),
+            commit_checks: None,
+            failures: 0,
+            finished_checks: None,
+            fetched_at: 0,
         }
     }
-}
 
-/// Shape of the `gh pr view --json ...` response we care about.
-#[derive(Deserialize)]
-struct GhPr {
-    number: u64,
-    url: String,
-    state: String,
-    #[serde(rename = "isDraft")]
-    is_draft: bool,
-    #[serde(rename = "statusCheckRollup", default)]
-    status_check_rollup: Vec<CheckItem>,
-}
+    #[test]
+    fn failed_refreshes_back_off() {
+        let ttl = Duration::from_secs(60);
+        assert_eq!(retry_after(ttl, 0), ttl);
+        assert_eq!(retry_after(ttl, 1), Duration::from_secs(120));
+        assert_eq!(retry_after(ttl, 3), Duration::from_secs(480));
+        assert_eq!(retry_after(ttl, 100), MAX_BACKOFF);
 
-fn write_cache(path: &Path, cache: &PrCache) {
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
+        // A TTL beyond the cap is never shortened.
+        let long = Duration::from_secs(3600);
+        assert_eq!(retry_after(long, 2), long);
     }
 
-    // Write to a temp file and rename so a concurrent reader never sees a
-    // half-written cache.
-    let tmp = path.with_extension("tmp");
-    if let Ok(mut file) = File::create(&tmp) {
-        if serde_json::to_writer(&mut file, cache).is_ok() && file.flush().is_ok() {
-            let _ = fs::rename(&tmp, path);
-        }
+    #[test]
+    fn notices_when_checks_finish() {
+        let pr = |checks| cache(checks).pr;
+        let finished = |before: &PrCache, after: Option<PrInfo>, announced_at| {
+            finished_checks(Some(before), after.as_ref(), announced_at).map(|f| f.status)
+        };
+
+        let pending = cache(Some(CheckStatus::Pending));
+        assert!(matches!(
+            finished(&pending, pr(Some(CheckStatus::Failure)), None),
+            Some(CheckStatus::Failure)
+        ));
+        assert!(finished(&pending, pr(Some(CheckStatus::Pending)), None).is_none());
+        assert!(finished(
+            &cache(Some(CheckStatus::Success)),
+            pr(Some(CheckStatus::Success)),
+            None
+        )
+        .is_none());
+
+        // Not announced yet, so it's kept until the checks run again.
+        let mut unannounced = cache(Some(CheckStatus::Success));
+        unannounced.finished_checks = Some(FinishedChecks {
+            status: CheckStatus::Success,
+            at: 100,
+        });
+        assert!(matches!(
+            finished(&unannounced, pr(Some(CheckStatus::Success)), Some(50)),
+            Some(CheckStatus::Success)
+        ));
+        assert!(finished(&unannounced, pr(Some(CheckStatus::Pending)), None).is_none());
+        // A prompt announced it, so it's dropped.
+        assert!(finished(&unannounced, pr(Some(CheckStatus::Success)), Some(100)).is_none());
+    }
+
+    #[test]
+    fn announces_finished_checks_once_without_touching_the_entry() {
+        let dir = TempDir::new("pr");
+        let cache_path = dir.join("pr-0.json");
+        std::fs::write(&cache_path, "written by the refresh").unwrap();
+
+        let mut entry = cache(Some(CheckStatus::Failure));
+        assert!(take_finished_checks(&entry, &cache_path).is_none());
+
+        entry.finished_checks = Some(FinishedChecks {
+            status: CheckStatus::Failure,
+            at: 100,
+        });
+        assert!(matches!(
+            take_finished_checks(&entry, &cache_path),
+            Some(CheckStatus::Failure)
+        ));
+        assert!(take_finished_checks(&entry, &cache_path).is_none());
+
+        // The checks ran and finished again.
+        entry.finished_checks = Some(FinishedChecks {
+            status: CheckStatus::Success,
+            at: 200,
+        });
+        assert!(matches!(
+            take_finished_checks(&entry, &cache_path),
+            Some(CheckStatus::Success)
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&cache_path).unwrap(),
+            "written by the refresh"
+        );
+    }
+
+    #[test]
+    fn configured_forges_pick_the_provider() {
+        let forges = [ForgeTemplate {
+            host: "git.corp.com".to_string(),
+            kind: Some(ForgeKind::GitLab),
+            branch_url: None,
+            compare_url: None,
+            commit_url: None,
+        }];
+        let provider = |url| detect_provider(Some(url), &forges);
+        assert_eq!(
+            provider("git@git.corp.com:team/repo.git"),
+            PrProvider::GitLab
+        );
+        assert_eq!(provider("https://gitlab.com/team/repo"), PrProvider::GitLab);
+        assert_eq!(provider("https://github.com/team/repo"), PrProvider::GitHub);
+        assert_eq!(
+            detect_provider(Some("git@git.corp.com:team/repo.git"), &[]),
+            PrProvider::GitHub
+        );
     }
 }
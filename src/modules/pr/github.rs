@@ -0,0 +1,336 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::{
+    tally, CheckCounts, CheckOutcome, CommitChecks, FetchFailed, Fetched, PrInfo, PrLookup,
+    PrState, ReviewDecision,
+};
+use crate::config::PrProvider;
+#[cfg(feature = "github-api")]
+use crate::modules::git::parse_remote;
+
+#[cfg(feature = "github-api")]
+mod api;
+
+/// The check runs and commit statuses on `$commit`, for branches with no PR.
+const COMMIT_QUERY: &str = "query($owner: String!, $name: String!, $commit: GitObjectID!) {
+  repository(owner: $owner, name: $name) {
+    object(oid: $commit) {
+      ... on Commit {
+        statusCheckRollup {
+          contexts(first: 100) {
+            nodes {
+              ... on CheckRun { status conclusion detailsUrl }
+              ... on StatusContext { state targetUrl }
+            }
+          }
+        }
+      }
+    }
+  }
+}";
+
+/// Looks up the PR for `branch`. Goes straight to the API when a token for the
+/// remote's host is available, and through the `gh` CLI otherwise.
+pub fn fetch_pr(
+    branch: &str,
+    repo_dir: &Path,
+    remote_url: Option<&str>,
+    lookup: &PrLookup,
+) -> Fetched<PrInfo> {
+    #[cfg(feature = "github-api")]
+    if let Some(client) = api::Client::new(remote_url, lookup) {
+        return client.fetch_pr(branch);
+    }
+    #[cfg(not(feature = "github-api"))]
+    let _ = (remote_url, lookup);
+
+    fetch_pr_with_gh(branch, repo_dir)
+}
+
+/// Looks up the CI results for `commit`, the same two ways as [`fetch_pr`].
+/// `None` if GitHub doesn't know the commit, e.g. because it isn't pushed.
+pub fn fetch_commit_checks(
+    commit: &str,
+    repo_dir: &Path,
+    remote_url: Option<&str>,
+    lookup: &PrLookup,
+) -> Fetched<CommitChecks> {
+    #[cfg(feature = "github-api")]
+    let data = match api::Client::new(remote_url, lookup) {
+        Some(client) => client.graphql(COMMIT_QUERY, &[("commit", commit)]),
+        None => graphql_with_gh(repo_dir, COMMIT_QUERY, &[("commit", commit)]),
+    };
+    #[cfg(not(feature = "github-api"))]
+    let data = {
+        let _ = (remote_url, lookup);
+        graphql_with_gh(repo_dir, COMMIT_QUERY, &[("commit", commit)])
+    };
+
+    let data: CommitData = data?;
+    let Some(commit_object) = data.repository.ok_or(FetchFailed)?.object else {
+        return Ok(None);
+    };
+    let rollup = commit_object.status_check_rollup;
+    let (counts, failed_check_url) =
+        summarize(rollup.map(|r| r.contexts.nodes).unwrap_or_default());
+    Ok(Some(CommitChecks {
+        commit: commit.to_string(),
+        counts,
+        failed_check_url,
+    }))
+}
+
+fn fetch_pr_with_gh(branch: &str, repo_dir: &Path) -> Fetched<PrInfo> {
+    let output = Command::new("gh")
+        .current_dir(repo_dir)
+        .args([
+            "pr",
+            "view",
+            branch,
+            "--json",
+            "number,url,state,isDraft,statusCheckRollup,reviewDecision,mergeable",
+        ])
+        .output()
+        .map_err(|_| FetchFailed)?;
+
+    // Anything but "there's no PR for this branch" is a failed lookup, such as
+    // an expired login or no network.
+    if !output.status.success() {
+        return if String::from_utf8_lossy(&output.stderr).contains("no pull requests found") {
+            Ok(None)
+        } else {
+            Err(FetchFailed)
+        };
+    }
+
+    let mut gh: GhPr = serde_json::from_slice(&output.stdout).map_err(|_| FetchFailed)?;
+    if gh.state == "OPEN" {
+        gh.review_threads = review_threads_with_gh(repo_dir, gh.number).ok();
+    }
+    Ok(Some(gh.into_info()))
+}
+
+/// `gh pr view` has no field for review threads, so they take a GraphQL query of
+/// their own.
+fn review_threads_with_gh(
+    repo_dir: &Path,
+    number: u64,
+) -> Result<Nodes<ReviewThread>, FetchFailed> {
+    const QUERY: &str = "query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) { reviewThreads(first: 100) { nodes { isResolved } } }
+  }
+}";
+
+    #[derive(Deserialize)]
+    struct Data {
+        repository: Repository,
+    }
+    #[derive(Deserialize)]
+    struct Repository {
+        #[serde(rename = "pullRequest")]
+        pull_request: PullRequest,
+    }
+    #[derive(Deserialize)]
+    struct PullRequest {
+        #[serde(rename = "reviewThreads")]
+        review_threads: Nodes<ReviewThread>,
+    }
+
+    let data: Data = graphql_with_gh(repo_dir, QUERY, &[("number", &number.to_string())])?;
+    Ok(data.repository.pull_request.review_threads)
+}
+
+/// Runs a GraphQL query through `gh api graphql` and returns its `data`. `gh`
+/// fills in the `$owner` and `$name` variables from the current repository;
+/// `variables` are passed with `-F`, so numbers keep their type.
+fn graphql_with_gh<T: DeserializeOwned>(
+    repo_dir: &Path,
+    query: &str,
+    variables: &[(&str, &str)],
+) -> Result<T, FetchFailed> {
+    let mut command = Command::new("gh");
+    command.current_dir(repo_dir).args([
+        "api",
+        "graphql",
+        "-F",
+        "owner={owner}",
+        "-F",
+        "name={repo}",
+    ]);
+    for (name, value) in variables {
+        command.arg("-F").arg(format!("{}={}", name, value));
+    }
+    let output = command
+        .arg("-f")
+        .arg(format!("query={}", query))
+        .output()
+        .map_err(|_| FetchFailed)?;
+
+    if !output.status.success() {
+        return Err(FetchFailed);
+    }
+
+    serde_json::from_slice::<GraphQlResponse<T>>(&output.stdout)
+        .ok()
+        .and_then(|response| response.data)
+        .ok_or(FetchFailed)
+}
+
+/// The envelope of a GraphQL response. `data` is missing when the query failed
+/// as a whole.
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+}
+
+/// A GraphQL connection, of which we only ever read the first page.
+#[derive(Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct ReviewThread {
+    #[serde(rename = "isResolved")]
+    is_resolved: bool,
+}
+
+#[derive(Deserialize)]
+struct CommitData {
+    repository: Option<CommitRepository>,
+}
+
+#[derive(Deserialize)]
+struct CommitRepository {
+    object: Option<Commit>,
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    #[serde(rename = "statusCheckRollup")]
+    status_check_rollup: Option<Rollup>,
+}
+
+#[derive(Deserialize)]
+struct Rollup {
+    contexts: Nodes<CheckItem>,
+}
+
+/// Counts `checks` by state, and picks the details page of the first failure.
+fn summarize(checks: Vec<CheckItem>) -> (CheckCounts, Option<String>) {
+    let counts = tally(checks.iter().map(CheckItem::outcome));
+    let failed_check_url = checks
+        .into_iter()
+        .filter(|check| matches!(check.outcome(), CheckOutcome::Failure))
+        .find_map(|check| check.details_url.or(check.target_url))
+        .filter(|url| !url.is_empty());
+    (counts, failed_check_url)
+}
+
+/// A single entry in GitHub's `statusCheckRollup`. Check runs report
+/// `status`/`conclusion` and a `detailsUrl`; legacy status contexts report
+/// `state` and a `targetUrl`.
+#[derive(Deserialize)]
+struct CheckItem {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(rename = "detailsUrl", default)]
+    details_url: Option<String>,
+    #[serde(rename = "targetUrl", default)]
+    target_url: Option<String>,
+}
+
+impl CheckItem {
+    fn outcome(&self) -> CheckOutcome {
+        // Legacy commit-status contexts carry a `state` instead of a status/
+        // conclusion pair.
+        if let Some(state) = &self.state {
+            return match state.as_str() {
+                "SUCCESS" => CheckOutcome::Success,
+                "PENDING" | "EXPECTED" => CheckOutcome::Pending,
+                _ => CheckOutcome::Failure, // FAILURE, ERROR
+            };
+        }
+
+        match self.status.as_deref() {
+            Some("COMPLETED") => match self.conclusion.as_deref() {
+                Some("SUCCESS") => CheckOutcome::Success,
+                // Skipped / neutral checks shouldn't tip the dot either way.
+                Some("SKIPPED") | Some("NEUTRAL") => CheckOutcome::Neutral,
+                // FAILURE, TIMED_OUT, CANCELLED, ACTION_REQUIRED, STARTUP_FAILURE
+                _ => CheckOutcome::Failure,
+            },
+            // QUEUED, IN_PROGRESS, WAITING, PENDING, REQUESTED, ...
+            Some(_) => CheckOutcome::Pending,
+            None => CheckOutcome::Neutral,
+        }
+    }
+}
+
+/// Shape of the `gh pr view --json ...` response we care about. The API backend
+/// builds the same thing from its GraphQL response.
+#[derive(Deserialize)]
+struct GhPr {
+    number: u64,
+    url: String,
+    state: String,
+    #[serde(rename = "isDraft")]
+    is_draft: bool,
+    #[serde(rename = "statusCheckRollup", default)]
+    status_check_rollup: Vec<CheckItem>,
+    /// `APPROVED`, `CHANGES_REQUESTED` or `REVIEW_REQUIRED`; empty or missing
+    /// when the repo doesn't require reviews.
+    #[serde(rename = "reviewDecision", default)]
+    review_decision: Option<String>,
+    /// `MERGEABLE`, `CONFLICTING` or `UNKNOWN` (not computed yet).
+    #[serde(default)]
+    mergeable: Option<String>,
+    #[serde(rename = "reviewThreads", default)]
+    review_threads: Option<Nodes<ReviewThread>>,
+}
+
+impl GhPr {
+    fn into_info(self) -> PrInfo {
+        // A draft PR is reported as OPEN with `isDraft: true`, so check that first.
+        let state = if self.is_draft {
+            PrState::Draft
+        } else {
+            match self.state.as_str() {
+                "MERGED" => PrState::Merged,
+                "CLOSED" => PrState::Closed,
+                _ => PrState::Open,
+            }
+        };
+
+        let (counts, failed_check_url) = summarize(self.status_check_rollup);
+
+        PrInfo {
+            number: self.number,
+            url: self.url,
+            state,
+            checks: counts.status(),
+            check_counts: counts,
+            failed_check_url,
+            provider: PrProvider::GitHub,
+            review: match self.review_decision.as_deref() {
+                Some("APPROVED") => Some(ReviewDecision::Approved),
+                Some("CHANGES_REQUESTED") => Some(ReviewDecision::ChangesRequested),
+                Some("REVIEW_REQUIRED") => Some(ReviewDecision::ReviewRequired),
+                _ => None,
+            },
+            unresolved_threads: self.review_threads.map_or(0, |threads| {
+                threads.nodes.iter().filter(|t| !t.is_resolved).count() as u32
+            }),
+            conflicts: self.mergeable.as_deref() == Some("CONFLICTING"),
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::ForgeKind;
+use crate::modules::git::forge_kind;
+use crate::modules::EnvGetter;
+use crate::platform::home_dir;
+
+use super::{
+    parse_remote, Commit, FetchFailed, Fetched, GhPr, GraphQlResponse, Nodes, PrInfo, PrLookup,
+    ReviewThread,
+};
+
+/// Generous, since this only ever runs in the background refresh process.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The newest PR whose head is `$branch`, with the check runs and commit
+/// statuses on its last commit, its review state and its review threads.
+const QUERY: &str = "query($owner: String!, $name: String!, $branch: String!) {
+  repository(owner: $owner, name: $name) {
+    pullRequests(headRefName: $branch, first: 1, orderBy: {field: CREATED_AT, direction: DESC}) {
+      nodes {
+        number
+        url
+        state
+        isDraft
+        reviewDecision
+        mergeable
+        reviewThreads(first: 100) { nodes { isResolved } }
+        commits(last: 1) {
+          nodes {
+            commit {
+              statusCheckRollup {
+                contexts(first: 100) {
+                  nodes {
+                    ... on CheckRun { status conclusion detailsUrl }
+                    ... on StatusContext { state targetUrl }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}";
+
+/// The GraphQL endpoint for `host`: `api.github.com` for github.com, and the
+/// `/api/graphql` path GitHub Enterprise Server uses otherwise.
+fn graphql_endpoint(host: &str) -> String {
+    if host == "github.com" {
+        String::from("https://api.github.com/graphql")
+    } else {
+        format!("https://{}/api/graphql", host)
+    }
+}
+
+/// A token for `host`, from the environment variables `gh` honours or else
+/// from `gh`'s own `hosts.yml`. Any host but github.com must be `configured` as
+/// GitHub Enterprise or listed in `hosts.yml`, so a token never goes to a host
+/// not known to run GitHub. Such a host gets the enterprise variables, and
+/// `GH_TOKEN`/`GITHUB_TOKEN` after them when it was configured - CI for an
+/// Enterprise instance tends to set just `GITHUB_TOKEN`. Tokens
+/// `gh` keeps in the system keyring aren't in that file; for those we fall
+/// back to running `gh`.
+fn find_token(host: &str, configured: bool, env: &EnvGetter, hosts: &str) -> Option<String> {
+    let vars: &[&str] = if host == "github.com" {
+        &["GH_TOKEN", "GITHUB_TOKEN"]
+    } else if configured {
+        &[
+            "GH_ENTERPRISE_TOKEN",
+            "GITHUB_ENTERPRISE_TOKEN",
+            "GH_TOKEN",
+            "GITHUB_TOKEN",
+        ]
+    } else if hosts_yml_lists(hosts, host) {
+        &["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+    } else {
+        return None;
+    };
+
+    vars.iter()
+        .find_map(|var| env(var).filter(|token| !token.is_empty()))
+        .or_else(|| hosts_yml_token(hosts, host))
+}
+
+fn gh_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("GH_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("gh"));
+    }
+    Some(home_dir()?.join(".config").join("gh"))
+}
+
+/// Whether `gh` is logged in to `host`, which then has a section of its own in
+/// `hosts.yml` (see [`hosts_yml_token`]).
+fn hosts_yml_lists(contents: &str, host: &str) -> bool {
+    contents
+        .lines()
+        .any(|line| !line.starts_with(char::is_whitespace) && is_section(line, host))
+}
+
+fn is_section(line: &str, host: &str) -> bool {
+    line.trim_end().strip_suffix(':') == Some(host)
+}
+
+/// Reads `host`'s `oauth_token` out of `gh`'s `hosts.yml`. The file is a flat
+/// map of hosts to indented key/value pairs, so there's no need for a full YAML
+/// parser:
+///
+/// ```yaml
+/// github.com:
+///     user: octocat
+///     oauth_token: gho_xxx
+/// ```
+fn hosts_yml_token(contents: &str, host: &str) -> Option<String> {
+    let mut in_host = false;
+    for line in contents.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            in_host = is_section(line, host);
+            continue;
+        }
+        if in_host {
+            if let Some(token) = line.trim().strip_prefix("oauth_token:") {
+                let token = token.trim().trim_matches(|c| c == '"' || c == '\'');
+                return (!token.is_empty()).then(|| token.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// A GraphQL endpoint, the token to use with it, and the repository to ask
+/// about.
+pub struct Client<'a> {
+    endpoint: String,
+    token: String,
+    owner: &'a str,
+    name: &'a str,
+}
+
+impl<'a> Client<'a> {
+    /// A client for the repository behind `remote_url`, or `None` if there's no
+    /// token for its host. The lookup's `api_url` overrides the endpoint, and
+    /// like a `github` entry in its `forges` marks an Enterprise host as one.
+    pub fn new(remote_url: Option<&'a str>, lookup: &PrLookup) -> Option<Client<'a>> {
+        let remote_url = remote_url?;
+        let (host, repo) = parse_remote(remote_url)?;
+        let (owner, name) = repo.split_once('/')?;
+        let configured = lookup.api_url.is_some()
+            || forge_kind(remote_url, lookup.forges) == Some(ForgeKind::GitHub);
+        let hosts = gh_config_dir()
+            .and_then(|dir| fs::read_to_string(dir.join("hosts.yml")).ok())
+            .unwrap_or_default();
+        Some(Client {
+            token: find_token(host, configured, &|var| env::var(var).ok(), &hosts)?,
+            endpoint: lookup
+                .api_url
+                .map_or_else(|| graphql_endpoint(host), str::to_string),
+            owner,
+            name,
+        })
+    }
+
+    /// Queries the PR for `branch`.
+    pub fn fetch_pr(&self, branch: &str) -> Fetched<PrInfo> {
+        let data: Data = self.graphql(QUERY, &[("branch", branch)])?;
+        let Some(pr) = data
+            .repository
+            .ok_or(FetchFailed)?
+            .pull_requests
+            .nodes
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        let checks = pr
+            .commits
+            .nodes
+            .into_iter()
+            .next()
+            .and_then(|node| node.commit.status_check_rollup)
+            .map(|rollup| rollup.contexts.nodes)
+            .unwrap_or_default();
+
+        let gh = GhPr {
+            number: pr.number,
+            url: pr.url,
+            state: pr.state,
+            is_draft: pr.is_draft,
+            status_check_rollup: checks,
+            review_decision: pr.review_decision,
+            mergeable: pr.mergeable,
+            review_threads: Some(pr.review_threads),
+        };
+        Ok(Some(gh.into_info()))
+    }
+
+    /// Runs `query` with `$owner` and `$name` set to the repository, plus
+    /// `variables`, and returns its `data`. HTTP errors (bad credentials, rate
+    /// limits) and GraphQL errors both count as a failed lookup.
+    pub fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: &[(&str, &str)],
+    ) -> Result<T, FetchFailed> {
+        let mut vars = json!({ "owner": self.owner, "name": self.name });
+        for (name, value) in variables {
+            vars[name] = json!(value);
+        }
+        let body = json!({ "query": query, "variables": vars });
+
+        let response = ureq::post(&self.endpoint)
+            .timeout(TIMEOUT)
+            .set("Authorization", &format!("bearer {}", self.token))
+            .set(
+                "User-Agent",
+                concat!("superline/", env!("CARGO_PKG_VERSION")),
+            )
+            .send_string(&body.to_string())
+            .map_err(|_| FetchFailed)?
+            .into_string()
+            .map_err(|_| FetchFailed)?;
+
+        serde_json::from_str::<GraphQlResponse<T>>(&response)
+            .ok()
+            .and_then(|response| response.data)
+            .ok_or(FetchFailed)
+    }
+}
+
+#[derive(Deserialize)]
+struct Data {
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    #[serde(rename = "pullRequests")]
+    pull_requests: Nodes<PullRequest>,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    number: u64,
+    url: String,
+    state: String,
+    #[serde(rename = "isDraft")]
+    is_draft: bool,
+    #[serde(rename = "reviewDecision")]
+    review_decision: Option<String>,
+    mergeable: Option<String>,
+    #[serde(rename = "reviewThreads")]
+    review_threads: Nodes<ReviewThread>,
+    commits: Nodes<CommitNode>,
+}
+
+#[derive(Deserialize)]
+struct CommitNode {
+    commit: Commit,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::modules::pr::github::{summarize, CommitData, COMMIT_QUERY};
+    use crate::modules::pr::{CheckStatus, PrState, ReviewDecision};
+
+    /// Serves a single request with `body`, handing back the request it got.
+    fn mock_server(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/graphql", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut payload = vec![0; content_length];
+            reader.read_exact(&mut payload).unwrap();
+            request.push_str(&String::from_utf8(payload).unwrap());
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request
+        });
+
+        (url, handle)
+    }
+
+    fn client(endpoint: String) -> Client<'static> {
+        Client {
+            endpoint,
+            token: "secret".into(),
+            owner: "a",
+            name: "b",
+        }
+    }
+
+    #[test]
+    fn fetches_pr_from_the_graphql_api() {
+        let (url, server) = mock_server(
+            r#"{"data":{"repository":{"pullRequests":{"nodes":[{
+                "number": 12,
+                "url": "https://github.com/a/b/pull/12",
+                "state": "OPEN",
+                "isDraft": false,
+                "reviewDecision": "CHANGES_REQUESTED",
+                "mergeable": "CONFLICTING",
+                "reviewThreads": {"nodes": [{"isResolved": false}, {"isResolved": true}, {"isResolved": false}]},
+                "commits": {"nodes": [{"commit": {"statusCheckRollup": {"contexts": {"nodes": [
+                    {"status": "COMPLETED", "conclusion": "SUCCESS", "detailsUrl": "https://ci/1"},
+                    {"status": "COMPLETED", "conclusion": "FAILURE", "detailsUrl": "https://ci/2"},
+                    {"state": "FAILURE", "targetUrl": "https://ci/3"},
+                    {"state": "PENDING"}
+                ]}}}}]}
+            }]}}}}"#,
+        );
+
+        let pr = client(url).fetch_pr("feature/x").ok().flatten().unwrap();
+        assert_eq!(pr.number, 12);
+        assert!(matches!(pr.state, PrState::Open));
+        assert!(matches!(pr.checks, Some(CheckStatus::Failure)));
+        assert_eq!(
+            (
+                pr.check_counts.success,
+                pr.check_counts.failure,
+                pr.check_counts.pending
+            ),
+            (1, 2, 1)
+        );
+        assert_eq!(pr.failed_check_url.as_deref(), Some("https://ci/2"));
+        assert!(matches!(pr.review, Some(ReviewDecision::ChangesRequested)));
+        assert_eq!(pr.unresolved_threads, 2);
+        assert!(pr.conflicts);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /api/graphql "));
+        assert!(request.contains("bearer secret"));
+        assert!(request.contains(r#""branch":"feature/x""#));
+    }
+
+    #[test]
+    fn no_pr_for_the_branch() {
+        let (url, server) = mock_server(r#"{"data":{"repository":{"pullRequests":{"nodes":[]}}}}"#);
+        assert!(matches!(client(url).fetch_pr("main"), Ok(None)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn fetches_commit_checks() {
+        let (url, server) = mock_server(
+            r#"{"data":{"repository":{"object":{"statusCheckRollup":{"contexts":{"nodes":[
+                {"status": "IN_PROGRESS", "conclusion": null, "detailsUrl": "https://ci/1"}
+            ]}}}}}}"#,
+        );
+
+        let data: CommitData = client(url)
+            .graphql(COMMIT_QUERY, &[("commit", "abc123")])
+            .ok()
+            .unwrap();
+        let rollup = data.repository.unwrap().object.unwrap().status_check_rollup;
+        let (counts, failed_url) = summarize(rollup.unwrap().contexts.nodes);
+        assert_eq!((counts.success, counts.failure, counts.pending), (0, 0, 1));
+        assert_eq!(failed_url, None);
+
+        let request = server.join().unwrap();
+        assert!(request.contains(r#""commit":"abc123""#));
+        assert!(request.contains(r#""owner":"a""#));
+    }
+
+    #[test]
+    fn reads_tokens_from_gh_hosts_yml() {
+        let hosts = "github.com:\n    user: octocat\n    oauth_token: gho_abc\n    git_protocol: https\nghe.corp.com:\n    oauth_token: \"ghe_def\"\n";
+        assert_eq!(hosts_yml_token(hosts, "github.com"), Some("gho_abc".into()));
+        assert_eq!(
+            hosts_yml_token(hosts, "ghe.corp.com"),
+            Some("ghe_def".into())
+        );
+        assert_eq!(hosts_yml_token(hosts, "gitlab.com"), None);
+        // Tokens stored in the keyring leave no `oauth_token` behind.
+        assert_eq!(
+            hosts_yml_token("github.com:\n    user: octocat\n", "github.com"),
+            None
+        );
+    }
+
+    #[test]
+    fn tokens_only_go_to_hosts_known_to_run_github() {
+        let env = |var: &str| Some(format!("{}-value", var));
+        let hosts = "ghe.corp.com:\n    user: octocat\n";
+        let token = |host, configured| find_token(host, configured, &env, hosts);
+
+        assert_eq!(
+            token("github.com", false).as_deref(),
+            Some("GH_TOKEN-value")
+        );
+        assert_eq!(
+            token("ghe.corp.com", false).as_deref(),
+            Some("GH_ENTERPRISE_TOKEN-value")
+        );
+        assert_eq!(
+            token("git.corp.com", true).as_deref(),
+            Some("GH_ENTERPRISE_TOKEN-value")
+        );
+        // Neither configured nor known to `gh`: could be any forge at all.
+        assert_eq!(token("git.corp.com", false), None);
+        assert_eq!(find_token("git.corp.com", false, &|_| None, hosts), None);
+
+        // A configured Enterprise host falls back on `GITHUB_TOKEN`, as in CI;
+        // one `gh` merely knows doesn't.
+        let ci = |var: &str| (var == "GITHUB_TOKEN").then(|| "ci-token".to_string());
+        assert_eq!(
+            find_token("git.corp.com", true, &ci, "").as_deref(),
+            Some("ci-token")
+        );
+        assert_eq!(find_token("ghe.corp.com", false, &ci, hosts), None);
+    }
+}
@@ -0,0 +1,275 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::config::PrProvider;
+
+use super::{
+    tally, CheckCounts, CheckOutcome, CommitChecks, FetchFailed, Fetched, PrInfo, PrState,
+    ReviewDecision,
+};
+
+/// Looks up the merge request for `branch` with `glab mr view`.
+pub fn fetch_mr(branch: &str, repo_dir: &Path) -> Fetched<PrInfo> {
+    let output = Command::new("glab")
+        .current_dir(repo_dir)
+        .args(["mr", "view", branch, "--output", "json"])
+        .output()
+        .map_err(|_| FetchFailed)?;
+
+    // As with `gh`, only "there's no MR" counts as an answer.
+    if !output.status.success() {
+        return if String::from_utf8_lossy(&output.stderr).contains("no open merge request") {
+            Ok(None)
+        } else {
+            Err(FetchFailed)
+        };
+    }
+
+    let mut mr = parse_mr(&output.stdout).ok_or(FetchFailed)?;
+    if matches!(mr.state, PrState::Open | PrState::Draft) {
+        mr.unresolved_threads = unresolved_discussions(repo_dir, mr.number).unwrap_or(0);
+    }
+    Ok(Some(mr))
+}
+
+/// `glab mr view` has no discussion counts, so the MR's discussions take an API
+/// call of their own.
+fn unresolved_discussions(repo_dir: &Path, iid: u64) -> Result<u32, FetchFailed> {
+    let output = Command::new("glab")
+        .current_dir(repo_dir)
+        .arg("api")
+        .arg(format!(
+            "projects/:id/merge_requests/{}/discussions?per_page=100",
+            iid
+        ))
+        .output()
+        .map_err(|_| FetchFailed)?;
+    if !output.status.success() {
+        return Err(FetchFailed);
+    }
+    count_unresolved(&output.stdout).ok_or(FetchFailed)
+}
+
+/// Discussions with a note that still needs resolving. Plain comments aren't
+/// resolvable at all.
+fn count_unresolved(json: &[u8]) -> Option<u32> {
+    let discussions: Vec<Discussion> = serde_json::from_slice(json).ok()?;
+    let unresolved = discussions
+        .iter()
+        .filter(|discussion| {
+            discussion
+                .notes
+                .iter()
+                .any(|note| note.resolvable && !note.resolved)
+        })
+        .count();
+    Some(unresolved as u32)
+}
+
+/// Looks up the pipeline for `commit`, for branches with no MR. `glab` fills in
+/// `:id` with the current project.
+pub fn fetch_commit_checks(commit: &str, repo_dir: &Path) -> Fetched<CommitChecks> {
+    let output = Command::new("glab")
+        .current_dir(repo_dir)
+        .arg("api")
+        .arg(format!("projects/:id/repository/commits/{}", commit))
+        .output()
+        .map_err(|_| FetchFailed)?;
+
+    if !output.status.success() {
+        return if is_unknown_commit(&String::from_utf8_lossy(&output.stderr)) {
+            Ok(None)
+        } else {
+            Err(FetchFailed)
+        };
+    }
+
+    let glab: GlabCommit = serde_json::from_slice(&output.stdout).map_err(|_| FetchFailed)?;
+    let (counts, failed_check_url) = summarize(glab.last_pipeline);
+    Ok(Some(CommitChecks {
+        commit: commit.to_string(),
+        counts,
+        failed_check_url,
+    }))
+}
+
+/// Whether `glab api` failed because GitLab doesn't know the commit, which
+/// hasn't been pushed then. It reports the API's `404 Commit Not Found` (or a
+/// plain `404 Not Found`) as `glab: 404 Commit Not Found (HTTP 404)`.
+fn is_unknown_commit(stderr: &str) -> bool {
+    stderr.contains("404 Commit Not Found") || stderr.contains("404 Not Found")
+}
+
+/// The pipeline is GitLab's one check; its page lists the failed jobs.
+fn summarize(pipeline: Option<Pipeline>) -> (CheckCounts, Option<String>) {
+    let counts = tally(pipeline.as_ref().map(Pipeline::outcome));
+    let failed_check_url = pipeline
+        .filter(|pipeline| matches!(pipeline.outcome(), CheckOutcome::Failure))
+        .and_then(|pipeline| pipeline.web_url);
+    (counts, failed_check_url)
+}
+
+fn parse_mr(json: &[u8]) -> Option<PrInfo> {
+    let mr: GlabMr = serde_json::from_slice(json).ok()?;
+
+    // Drafts are `opened` with `draft: true`; older GitLab versions only set
+    // `work_in_progress`.
+    let state = match mr.state.as_str() {
+        "merged" => PrState::Merged,
+        "closed" | "locked" => PrState::Closed,
+        _ if mr.draft || mr.work_in_progress => PrState::Draft,
+        _ => PrState::Open,
+    };
+
+    let (counts, failed_check_url) = summarize(mr.head_pipeline);
+
+    Some(PrInfo {
+        number: mr.iid,
+        url: mr.web_url,
+        state,
+        checks: counts.status(),
+        check_counts: counts,
+        failed_check_url,
+        provider: PrProvider::GitLab,
+        // GitLab has no overall approval state, only the reason an MR is
+        // blocked.
+        review: match mr.detailed_merge_status.as_deref() {
+            Some("requested_changes") => Some(ReviewDecision::ChangesRequested),
+            Some("not_approved") => Some(ReviewDecision::ReviewRequired),
+            _ => None,
+        },
+        // Filled in by `fetch_mr`, which looks up the discussions.
+        unresolved_threads: 0,
+        conflicts: mr.has_conflicts,
+    })
+}
+
+/// Shape of the `glab mr view --output json` response we care about.
+#[derive(Deserialize)]
+struct GlabMr {
+    iid: u64,
+    web_url: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    work_in_progress: bool,
+    #[serde(default)]
+    head_pipeline: Option<Pipeline>,
+    #[serde(default)]
+    has_conflicts: bool,
+    #[serde(default)]
+    detailed_merge_status: Option<String>,
+}
+
+/// Shape of the `glab api projects/:id/repository/commits/<sha>` response.
+#[derive(Deserialize)]
+struct GlabCommit {
+    #[serde(default)]
+    last_pipeline: Option<Pipeline>,
+}
+
+/// Shape of the `glab api projects/:id/merge_requests/<iid>/discussions`
+/// response.
+#[derive(Deserialize)]
+struct Discussion {
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+#[derive(Deserialize)]
+struct Note {
+    #[serde(default)]
+    resolvable: bool,
+    #[serde(default)]
+    resolved: bool,
+}
+
+#[derive(Deserialize)]
+struct Pipeline {
+    status: String,
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
+impl Pipeline {
+    fn outcome(&self) -> CheckOutcome {
+        match self.status.as_str() {
+            "success" => CheckOutcome::Success,
+            "failed" | "canceled" => CheckOutcome::Failure,
+            // Skipped pipelines and ones waiting on a manual job shouldn't tip
+            // the dot either way.
+            "skipped" | "manual" => CheckOutcome::Neutral,
+            // created, waiting_for_resource, preparing, pending, running, scheduled
+            _ => CheckOutcome::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::pr::CheckStatus;
+
+    #[test]
+    fn parses_glab_output() {
+        let json = br#"{
+            "iid": 42,
+            "web_url": "https://gitlab.com/g/p/-/merge_requests/42",
+            "state": "opened",
+            "draft": true,
+            "has_conflicts": true,
+            "detailed_merge_status": "not_approved",
+            "head_pipeline": {"id": 7, "status": "failed", "web_url": "https://gitlab.com/g/p/-/pipelines/7"}
+        }"#;
+        let mr = parse_mr(json).unwrap();
+        assert_eq!(mr.number, 42);
+        assert!(matches!(mr.state, PrState::Draft));
+        assert!(matches!(mr.checks, Some(CheckStatus::Failure)));
+        assert_eq!(
+            mr.failed_check_url.as_deref(),
+            Some("https://gitlab.com/g/p/-/pipelines/7")
+        );
+        assert_eq!(mr.provider, PrProvider::GitLab);
+        assert!(matches!(mr.review, Some(ReviewDecision::ReviewRequired)));
+        assert!(mr.conflicts);
+
+        let merged =
+            parse_mr(br#"{"iid": 1, "web_url": "u", "state": "merged", "draft": true}"#).unwrap();
+        assert!(matches!(merged.state, PrState::Merged));
+        assert!(merged.checks.is_none());
+    }
+
+    #[test]
+    fn counts_unresolved_discussions() {
+        let json = br#"[
+            {"id": "a", "notes": [{"body": "looks good", "resolvable": false}]},
+            {"id": "b", "notes": [
+                {"body": "rename this?", "resolvable": true, "resolved": false},
+                {"body": "why?", "resolvable": true, "resolved": false}
+            ]},
+            {"id": "c", "notes": [{"body": "typo", "resolvable": true, "resolved": true}]},
+            {"id": "d", "notes": [{"body": "add a test", "resolvable": true, "resolved": false}]}
+        ]"#;
+        assert_eq!(count_unresolved(json), Some(2));
+        assert_eq!(count_unresolved(b"[]"), Some(0));
+        assert_eq!(count_unresolved(b"{\"message\": \"404 Not Found\"}"), None);
+    }
+
+    #[test]
+    fn tells_unpushed_commits_from_other_errors() {
+        assert!(is_unknown_commit("glab: 404 Commit Not Found (HTTP 404)\n"));
+        assert!(is_unknown_commit("glab: 404 Not Found (HTTP 404)\n"));
+        assert!(!is_unknown_commit(
+            "glab: 404 Project Not Found (HTTP 404)\n"
+        ));
+        assert!(!is_unknown_commit(
+            "dial tcp 10.0.0.1:4040: connect: connection refused\n"
+        ));
+        assert!(!is_unknown_commit(
+            "glab: 401 Unauthorized (HTTP 401) for commit 5404e1c\n"
+        ));
+    }
+}
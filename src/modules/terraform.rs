@@ -0,0 +1,207 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::colors::Color;
+use crate::themes::DefaultColors;
+use crate::utils::glob_match;
+use crate::{Powerline, Style};
+
+use super::{EnvGetter, Module};
+
+/// Shows the selected Terraform (or OpenTofu) workspace in directories holding
+/// a configuration, so `apply` doesn't go to the wrong one.
+pub struct Terraform<S> {
+    show_version: bool,
+    /// Glob patterns for the workspaces to show in the alert colours.
+    production: Vec<String>,
+    scheme: PhantomData<S>,
+}
+
+pub trait TerraformScheme: DefaultColors {
+    fn terraform_fg() -> Color {
+        Self::default_fg()
+    }
+    fn terraform_bg() -> Color {
+        Self::default_bg()
+    }
+    fn terraform_production_fg() -> Color {
+        Self::alert_fg()
+    }
+    fn terraform_production_bg() -> Color {
+        Self::alert_bg()
+    }
+}
+
+impl<S: TerraformScheme> Terraform<S> {
+    pub fn new(show_version: bool, production: Vec<String>) -> Terraform<S> {
+        Terraform {
+            show_version,
+            production,
+            scheme: PhantomData,
+        }
+    }
+}
+
+const TERRAFORM_ICON: &str = "\u{f1062}"; // nf-md-terraform
+
+impl<S: TerraformScheme> Module for Terraform<S> {
+    fn append_segments(&mut self, powerline: &mut Powerline) {
+        let Ok(cwd) = std::env::current_dir() else {
+            return;
+        };
+        let env = |key: &str| std::env::var(key).ok();
+        let Some(status) = read_status(&env, &cwd, self.show_version) else {
+            return;
+        };
+
+        let mut label = format!("{} {}", TERRAFORM_ICON, status.workspace);
+        if let Some(version) = &status.required_version {
+            label += &format!(" ({})", version);
+        }
+
+        let production = self
+            .production
+            .iter()
+            .any(|pattern| glob_match(pattern, &status.workspace));
+        let style = if production {
+            Style::simple(S::terraform_production_fg(), S::terraform_production_bg())
+        } else {
+            Style::simple(S::terraform_fg(), S::terraform_bg())
+        };
+        powerline.add_segment(label, style);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct TerraformStatus {
+    workspace: String,
+    required_version: Option<String>,
+}
+
+/// `None` unless `dir` holds `.tf`/`.tofu` files or an initialised data
+/// directory. The workspace comes from `TF_WORKSPACE`, then the data
+/// directory's `environment` file - which is what `terraform workspace select`
+/// writes - and is `default` otherwise.
+fn read_status(env: &EnvGetter, dir: &Path, with_version: bool) -> Option<TerraformStatus> {
+    let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    let data_dir = match var("TF_DATA_DIR") {
+        Some(data_dir) => dir.join(data_dir),
+        None => dir.join(".terraform"),
+    };
+    let sources = config_files(dir);
+    if sources.is_empty() && !data_dir.is_dir() {
+        return None;
+    }
+
+    let workspace = var("TF_WORKSPACE")
+        .or_else(|| {
+            let selected = fs::read_to_string(data_dir.join("environment")).ok()?;
+            Some(selected.trim().to_string()).filter(|name| !name.is_empty())
+        })
+        .unwrap_or_else(|| "default".to_string());
+    let required_version = if with_version {
+        sources
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .find_map(|source| required_version(&source))
+    } else {
+        None
+    };
+
+    Some(TerraformStatus {
+        workspace,
+        required_version,
+    })
+}
+
+fn config_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("tf" | "tofu")
+            ) && path.is_file()
+        })
+        .collect();
+    // `read_dir` order is arbitrary; sort so the constraint picked is stable.
+    files.sort();
+    files
+}
+
+/// The `required_version` constraint from a `terraform { ... }` block. Not a
+/// full HCL parser: the setting doesn't appear anywhere else, so matching the
+/// attribute is enough.
+fn required_version(source: &str) -> Option<String> {
+    let pattern = Regex::new(r#"(?m)^\s*required_version\s*=\s*"([^"]*)""#).ok()?;
+    pattern
+        .captures(source)
+        .map(|captures| captures[1].trim().to_string())
+        .filter(|version| !version.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn reads_the_selected_workspace() {
+        let dir = TempDir::new("terraform");
+        assert_eq!(read_status(&|_| None, &dir, true), None);
+
+        fs::write(
+            dir.join("versions.tf"),
+            "terraform {\n  required_version = \">= 1.5, < 2.0\"\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_status(&|_| None, &dir, true),
+            Some(TerraformStatus {
+                workspace: "default".to_string(),
+                required_version: Some(">= 1.5, < 2.0".to_string()),
+            })
+        );
+
+        fs::create_dir_all(dir.join(".terraform")).unwrap();
+        fs::write(dir.join(".terraform").join("environment"), "production").unwrap();
+        assert_eq!(
+            read_status(&|_| None, &dir, false),
+            Some(TerraformStatus {
+                workspace: "production".to_string(),
+                required_version: None,
+            })
+        );
+
+        // Terraform itself prefers the environment variable to the file.
+        let env = |key: &str| (key == "TF_WORKSPACE").then(|| "staging".to_string());
+        assert_eq!(
+            read_status(&env, &dir, false).map(|status| status.workspace),
+            Some("staging".to_string())
+        );
+    }
+
+    #[test]
+    fn follows_tf_data_dir() {
+        let dir = TempDir::new("terraform");
+        fs::create_dir_all(dir.join("state")).unwrap();
+        fs::write(dir.join("state").join("environment"), "blue\n").unwrap();
+
+        let env = |key: &str| (key == "TF_DATA_DIR").then(|| "state".to_string());
+        assert_eq!(
+            read_status(&env, &dir, true),
+            Some(TerraformStatus {
+                workspace: "blue".to_string(),
+                required_version: None,
+            })
+        );
+    }
+}
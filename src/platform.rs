@@ -9,7 +9,7 @@
 //! Unix and Windows resolution rules regardless of the host they run on.
 
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A function that looks up an environment variable, mirroring
 /// [`std::env::var_os`]. Taken as a parameter so tests can inject a fake
@@ -127,6 +127,34 @@ pub fn cwd_is_readonly() -> bool {
     false
 }
 
+/// Space left for unprivileged users and total size, in bytes, of the
+/// filesystem holding `path` - the figures `df` shows.
+#[cfg(unix)]
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    // The field types differ between platforms.
+    #[allow(clippy::unnecessary_cast)]
+    let (available, blocks, block_size) = (
+        stat.f_bavail as u64,
+        stat.f_blocks as u64,
+        stat.f_frsize as u64,
+    );
+    Some((available * block_size, blocks * block_size))
+}
+
+#[cfg(not(unix))]
+pub fn disk_space(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
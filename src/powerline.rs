@@ -4,10 +4,11 @@ use std::time::Duration;
 
 use crate::colors::Color;
 use crate::config;
-use crate::config::{LineSegment, SeparatorStyle, TerminalRuntimeMetadata};
+use crate::config::{LineSegment, NotifyStyle, SeparatorStyle, TerminalRuntimeMetadata};
 use crate::modules::{
-    Cargo, Cmd, Cwd, Git, Host, LastCmdDuration, Module, Nvm, Pr, PythonEnv, ReadOnly, SdkmanJava,
-    ShellName, Spacer, Time, User,
+    Aws, Azure, Battery, Cargo, Cmd, Container, Cwd, Disk, Docker, Gcloud, Git, GitCommit,
+    GitIdentity, GitTicket, Hg, Host, Jj, Jobs, Kube, LastCmdDuration, Load, Memory, Module, Nix,
+    Nvm, Pr, PythonEnv, ReadOnly, SdkmanJava, ShellName, Spacer, Terraform, Time, User,
 };
 use crate::terminal::*;
 use crate::themes::CompleteTheme;
@@ -29,6 +30,29 @@ impl Style {
     }
 }
 
+/// A coloured glyph tucked into a hyperlink segment after its label, see
+/// [`Powerline::add_hyperlink_segment`]. It can carry a link of its own.
+#[derive(Clone, Copy)]
+pub struct Marker<'a> {
+    pub glyph: &'a str,
+    pub color: Color,
+    pub url: Option<&'a str>,
+}
+
+impl<'a> Marker<'a> {
+    pub fn new(glyph: &'a str, color: Color) -> Marker<'a> {
+        Marker {
+            glyph,
+            color,
+            url: None,
+        }
+    }
+
+    pub fn link(self, url: Option<&'a str>) -> Marker<'a> {
+        Marker { url, ..self }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum Separator {
     Chevron,
@@ -167,11 +191,11 @@ impl Powerline {
         runtime_data: impl TerminalRuntimeMetadata,
     ) -> Self {
         let mut powerline = Powerline::new();
-        powerline.add_conf_modules::<T>(&conf.left, &runtime_data);
+        powerline.add_conf_modules::<T>(&conf.left, conf, &runtime_data);
 
         if let Some(right_modules) = &conf.right {
             powerline.start_right();
-            powerline.add_conf_modules::<T>(right_modules, &runtime_data);
+            powerline.add_conf_modules::<T>(right_modules, conf, &runtime_data);
         }
 
         powerline
@@ -291,36 +315,73 @@ impl Powerline {
     }
 
     /// Adds a segment whose text is an OSC 8 terminal hyperlink, optionally
-    /// followed by a coloured marker glyph (e.g. the PR status dot) that shares
-    /// this segment's background instead of getting one of its own. The OSC and
-    /// colour escapes are invisible, so the visible width is computed from
-    /// `label` and the marker glyph alone to keep column accounting (and
+    /// followed by coloured marker glyphs (e.g. the PR status dot) that share
+    /// this segment's background instead of getting one of their own. The OSC
+    /// and colour escapes are invisible, so the visible width is computed from
+    /// `label` and the marker glyphs alone to keep column accounting (and
     /// right-prompt padding) correct.
     pub fn add_hyperlink_segment(
         &mut self,
         label: &str,
         url: &str,
         style: Style,
-        marker: Option<(&str, Color)>,
+        markers: &[Marker],
+    ) {
+        self.add_marked_segment_text(hyperlink(url, label), label, style, markers);
+    }
+
+    /// Like [`Powerline::add_hyperlink_segment`], for a label that isn't a link.
+    pub fn add_marked_segment(&mut self, label: &str, style: Style, markers: &[Marker]) {
+        self.add_marked_segment_text(label.to_string(), label, style, markers);
+    }
+
+    fn add_marked_segment_text(
+        &mut self,
+        mut seg: String,
+        label: &str,
+        style: Style,
+        markers: &[Marker],
     ) {
         let mut visible_width = label.chars().count();
-        let link = format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, label);
-        let seg = match marker {
-            Some((glyph, color)) => {
-                // separating space + the glyph itself
-                visible_width += 1 + glyph.chars().count();
-                // Colour the glyph, then restore the segment's foreground so the
-                // terminal state matches what the renderer records for it.
-                format!("{} {}{}{}", link, FgColor::from(color), glyph, style.fg)
-            }
-            None => link,
-        };
+        for marker in markers {
+            // separating space + the glyph itself
+            visible_width += 1 + marker.glyph.chars().count();
+            let glyph = match marker.url {
+                Some(url) => hyperlink(url, marker.glyph),
+                None => marker.glyph.to_string(),
+            };
+            // Colour the glyph, then restore the segment's foreground so the
+            // terminal state matches what the renderer records for it.
+            let _ = write!(seg, " {}{}{}", FgColor::from(marker.color), glyph, style.fg);
+        }
         let _ = match self.direction {
             Direction::Left => self.write_segment(seg, style, true, Some(visible_width)),
             Direction::Right => self.write_segment_right(seg, style, true, Some(visible_width)),
         };
     }
 
+    /// Sends a desktop notification through the terminal. It takes no columns,
+    /// so it can go anywhere in the prompt.
+    pub fn notify(&mut self, style: NotifyStyle, title: &str, body: &str) {
+        let escape = match style {
+            NotifyStyle::Osc9 => format!("\x1b]9;{}: {}\x07", title, body),
+            NotifyStyle::Osc777 => format!("\x1b]777;notify;{};{}\x07", title, body),
+        };
+        self.write_invisible(&escape);
+    }
+
+    pub fn bell(&mut self) {
+        self.write_invisible("\x07");
+    }
+
+    fn write_invisible(&mut self, escape: &str) {
+        let buffer = match self.direction {
+            Direction::Left => &mut self.left_buffer,
+            Direction::Right => &mut self.right_buffer,
+        };
+        let _ = write!(buffer, "{}", Invisible(escape));
+    }
+
     pub fn start_right(&mut self) {
         assert_eq!(self.direction, Direction::Left);
         self.close_left_buffer();
@@ -334,6 +395,7 @@ impl Powerline {
     fn add_conf_modules<T: CompleteTheme>(
         &mut self,
         modules: &Vec<LineSegment>,
+        line: &config::CommandLine,
         runtime_data: &impl TerminalRuntimeMetadata,
     ) {
         for module in modules {
@@ -345,11 +407,103 @@ impl Powerline {
                     self.add_module(Cmd::<T>::new(runtime_data.last_command_status()))
                 }
                 LineSegment::Cargo => self.add_module(Cargo::<T>::new()),
-                LineSegment::Git => self.add_module(Git::<T>::new()),
-                LineSegment::Pr { status } => self.add_module(Pr::<T>::new(*status)),
+                LineSegment::Jobs => self.add_module(Jobs::<T>::new(
+                    runtime_data.background_jobs(),
+                    runtime_data.directory_stack_depth(),
+                )),
+                LineSegment::Git {
+                    submodules,
+                    worktrees,
+                    link,
+                    forges,
+                } => self.add_module(
+                    Git::<T>::new()
+                        .submodules(*submodules)
+                        .worktrees(*worktrees)
+                        .link(*link, forges.clone())
+                        // It steps aside for a `jj` segment on the same line.
+                        .hide_in_jj(line.segments().any(|m| matches!(m, LineSegment::Jj))),
+                ),
+                LineSegment::GitCommit { forges } => {
+                    self.add_module(GitCommit::<T>::new(forges.clone()))
+                }
+                LineSegment::GitIdentity {
+                    rules,
+                    only_on_mismatch,
+                    signing_key,
+                } => self.add_module(GitIdentity::<T>::new(
+                    rules.clone(),
+                    *only_on_mismatch,
+                    *signing_key,
+                )),
+                LineSegment::GitTicket { pattern, url } => {
+                    self.add_module(GitTicket::<T>::new(pattern.clone(), url.clone()))
+                }
+                LineSegment::Kube {
+                    aliases,
+                    production,
+                    namespace,
+                } => self.add_module(Kube::<T>::new(
+                    aliases.clone(),
+                    production.clone(),
+                    *namespace,
+                )),
+                LineSegment::Aws { region, expiry } => {
+                    self.add_module(Aws::<T>::new(*region, *expiry))
+                }
+                LineSegment::Gcloud {
+                    account,
+                    production,
+                } => self.add_module(Gcloud::<T>::new(*account, production.clone())),
+                LineSegment::Azure {
+                    account,
+                    production,
+                } => self.add_module(Azure::<T>::new(*account, production.clone())),
+                LineSegment::Terraform {
+                    version,
+                    production,
+                } => self.add_module(Terraform::<T>::new(*version, production.clone())),
+                LineSegment::Hg { timeout_ms, remote } => {
+                    self.add_module(Hg::<T>::new(Duration::from_millis(*timeout_ms), *remote))
+                }
+                LineSegment::Jj => self.add_module(Jj::<T>::new()),
+                LineSegment::Pr {
+                    status,
+                    review,
+                    check_counts,
+                    ci,
+                    provider,
+                    api_url,
+                    cache_ttl,
+                    refresh_debounce,
+                    skip_branches,
+                    notify,
+                    bell,
+                } => self.add_module(
+                    Pr::<T>::new(*status, *review, *provider, api_url.clone())
+                        .check_counts(*check_counts)
+                        .ci(*ci)
+                        .cache(
+                            Duration::from_secs(*cache_ttl),
+                            Duration::from_secs(*refresh_debounce),
+                        )
+                        .skip_branches(skip_branches.clone())
+                        .notify(*notify, *bell)
+                        .forges(line.forges()),
+                ),
                 LineSegment::Separator(style) => self.set_separator(style.into()),
                 LineSegment::ReadOnly => self.add_module(ReadOnly::<T>::new()),
                 LineSegment::Host => self.add_module(Host::<T>::new()),
+                LineSegment::Battery {
+                    hide_above,
+                    warning,
+                    critical,
+                } => self.add_module(Battery::<T>::new(*hide_above, *warning, *critical)),
+                LineSegment::Container => self.add_module(Container::<T>::new()),
+                LineSegment::Docker => self.add_module(Docker::<T>::new()),
+                LineSegment::Load { above } => self.add_module(Load::<T>::new(*above)),
+                LineSegment::Memory { above } => self.add_module(Memory::<T>::new(*above)),
+                LineSegment::Disk { below } => self.add_module(Disk::<T>::new(*below)),
                 LineSegment::Shell => {
                     self.add_module(ShellName::<T>::new(runtime_data.shell_name()))
                 }
@@ -375,6 +529,7 @@ impl Powerline {
                     *resolve_symlinks,
                 )),
                 LineSegment::Nvm => self.add_module(Nvm::<T>::new()),
+                LineSegment::Nix { direnv } => self.add_module(Nix::<T>::new(*direnv)),
                 LineSegment::Sdkman => self.add_module(SdkmanJava::<T>::new()),
             };
         }
@@ -467,3 +622,8 @@ impl Powerline {
         self.last_style = None;
     }
 }
+
+/// Wraps `text` in an OSC 8 hyperlink to `url`.
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
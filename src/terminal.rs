@@ -19,6 +19,9 @@ pub struct FgColor(u8);
 
 pub struct Reset;
 
+/// An escape sequence that takes up no columns, such as a notification.
+pub struct Invisible<'a>(pub &'a str);
+
 impl FgColor {
     pub fn transpose(self) -> BgColor {
         BgColor(self.0)
@@ -72,3 +75,13 @@ impl std::fmt::Display for Reset {
         }
     }
 }
+
+impl std::fmt::Display for Invisible<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match SHELL.get().expect("shell not specified!") {
+            Shell::Bash => write!(f, r#"\[{}\]"#, self.0),
+            Shell::Bare => f.write_str(self.0),
+            Shell::Zsh => write!(f, "%{{{}%}}", self.0),
+        }
+    }
+}
@@ -4,9 +4,12 @@ pub use simple::SimpleTheme;
 
 use crate::colors::Color;
 use crate::modules::{
-    CargoScheme, CmdScheme, CwdScheme, ExitCodeScheme, GitScheme, HostScheme,
-    LastCmdDurationScheme, NvmScheme, PrScheme, PythonEnvScheme, ReadOnlyScheme, SdkmanScheme,
-    ShellScheme, SpacerScheme, TimeScheme, UserScheme,
+    AwsScheme, AzureScheme, BatteryScheme, CargoScheme, CmdScheme, ContainerScheme, CwdScheme,
+    DiskScheme, DockerScheme, ExitCodeScheme, GcloudScheme, GitCommitScheme, GitIdentityScheme,
+    GitScheme, GitTicketScheme, HgScheme, HostScheme, JjScheme, JobsScheme, KubeScheme,
+    LastCmdDurationScheme, LoadScheme, MemoryScheme, NixScheme, NvmScheme, PrScheme,
+    PythonEnvScheme, ReadOnlyScheme, SdkmanScheme, ShellScheme, SpacerScheme, TerraformScheme,
+    TimeScheme, UserScheme,
 };
 
 mod custom;
@@ -41,6 +44,24 @@ pub trait CompleteTheme:
     + LastCmdDurationScheme
     + ExitCodeScheme
     + GitScheme
+    + GitCommitScheme
+    + GitIdentityScheme
+    + GitTicketScheme
+    + HgScheme
+    + JjScheme
+    + KubeScheme
+    + AwsScheme
+    + GcloudScheme
+    + AzureScheme
+    + TerraformScheme
+    + ContainerScheme
+    + NixScheme
+    + DockerScheme
+    + BatteryScheme
+    + LoadScheme
+    + MemoryScheme
+    + DiskScheme
+    + JobsScheme
     + PrScheme
     + PythonEnvScheme
     + ReadOnlyScheme
@@ -9,9 +9,12 @@ use serde_json::Value;
 
 use crate::colors::Color;
 use crate::modules::{
-    CargoScheme, CmdScheme, CwdScheme, ExitCodeScheme, GitScheme, HostScheme,
-    LastCmdDurationScheme, NvmScheme, PrScheme, PythonEnvScheme, ReadOnlyScheme, SdkmanScheme,
-    ShellScheme, SpacerScheme, TimeScheme, UserScheme,
+    AwsScheme, AzureScheme, BatteryScheme, CargoScheme, CmdScheme, ContainerScheme, CwdScheme,
+    DiskScheme, DockerScheme, ExitCodeScheme, GcloudScheme, GitCommitScheme, GitIdentityScheme,
+    GitScheme, GitTicketScheme, HgScheme, HostScheme, JjScheme, JobsScheme, KubeScheme,
+    LastCmdDurationScheme, LoadScheme, MemoryScheme, NixScheme, NvmScheme, PrScheme,
+    PythonEnvScheme, ReadOnlyScheme, SdkmanScheme, ShellScheme, SpacerScheme, TerraformScheme,
+    TimeScheme, UserScheme,
 };
 use crate::themes::{CompleteTheme, DefaultColors};
 
@@ -43,6 +46,10 @@ impl From<&ColorsJson> for Color {
 struct DefaultColorsJson {
     fg: ColorsJson,
     bg: ColorsJson,
+    #[serde(default)]
+    alert_fg: Option<ColorsJson>,
+    #[serde(default)]
+    alert_bg: Option<ColorsJson>,
 }
 
 #[derive(Deserialize)]
@@ -136,6 +143,24 @@ impl DefaultColors for CustomTheme {
         let theme = THEME.get().expect("custom theme not set");
         (&theme.defaults.fg).into()
     }
+
+    fn alert_bg() -> Color {
+        let theme = THEME.get().expect("custom theme not set");
+        theme
+            .defaults
+            .alert_bg
+            .as_ref()
+            .map_or_else(Self::default_bg, Into::into)
+    }
+
+    fn alert_fg() -> Color {
+        let theme = THEME.get().expect("custom theme not set");
+        theme
+            .defaults
+            .alert_fg
+            .as_ref()
+            .map_or_else(Self::default_fg, Into::into)
+    }
 }
 
 impl CompleteTheme for CustomTheme {}
@@ -217,6 +242,150 @@ impl GitScheme for CustomTheme {
     color_from_json!(git_repo_clean_fg, git, clean_fg, default_fg);
     color_from_json!(git_repo_dirty_bg, git, dirty_bg, default_bg);
     color_from_json!(git_repo_dirty_fg, git, dirty_fg, default_fg);
+    color_from_json!(git_submodule_bg, git, submodule_bg, default_bg);
+    color_from_json!(git_submodule_fg, git, submodule_fg, default_fg);
+    color_from_json!(git_worktrees_bg, git, worktrees_bg, default_bg);
+    color_from_json!(git_worktrees_fg, git, worktrees_fg, default_fg);
+}
+
+impl GitCommitScheme for CustomTheme {
+    color_from_json!(git_commit_fg, git_commit, fg, default_fg);
+    color_from_json!(git_commit_bg, git_commit, bg, default_bg);
+}
+
+impl GitTicketScheme for CustomTheme {
+    color_from_json!(git_ticket_fg, git_ticket, fg, default_fg);
+    color_from_json!(git_ticket_bg, git_ticket, bg, default_bg);
+}
+
+impl GitIdentityScheme for CustomTheme {
+    color_from_json!(git_identity_fg, git_identity, fg, default_fg);
+    color_from_json!(git_identity_bg, git_identity, bg, default_bg);
+    color_from_json!(
+        git_identity_mismatch_fg,
+        git_identity,
+        mismatch_fg,
+        alert_fg
+    );
+    color_from_json!(
+        git_identity_mismatch_bg,
+        git_identity,
+        mismatch_bg,
+        alert_bg
+    );
+}
+
+impl HgScheme for CustomTheme {
+    color_from_json!(hg_clean_bg, hg, clean_bg, default_bg);
+    color_from_json!(hg_clean_fg, hg, clean_fg, default_fg);
+    color_from_json!(hg_dirty_bg, hg, dirty_bg, default_bg);
+    color_from_json!(hg_dirty_fg, hg, dirty_fg, default_fg);
+    color_from_json!(hg_modified_bg, hg, modified_bg, default_bg);
+    color_from_json!(hg_modified_fg, hg, modified_fg, default_fg);
+    color_from_json!(hg_added_bg, hg, added_bg, default_bg);
+    color_from_json!(hg_added_fg, hg, added_fg, default_fg);
+    color_from_json!(hg_removed_bg, hg, removed_bg, default_bg);
+    color_from_json!(hg_removed_fg, hg, removed_fg, default_fg);
+    color_from_json!(hg_unknown_bg, hg, unknown_bg, default_bg);
+    color_from_json!(hg_unknown_fg, hg, unknown_fg, default_fg);
+    color_from_json!(hg_remote_bg, hg, remote_bg, default_bg);
+    color_from_json!(hg_remote_fg, hg, remote_fg, default_fg);
+}
+
+impl JjScheme for CustomTheme {
+    color_from_json!(jj_clean_fg, jj, clean_fg, default_fg);
+    color_from_json!(jj_clean_bg, jj, clean_bg, default_bg);
+    color_from_json!(jj_changed_fg, jj, changed_fg, default_fg);
+    color_from_json!(jj_changed_bg, jj, changed_bg, default_bg);
+    color_from_json!(jj_conflict_fg, jj, conflict_fg, alert_fg);
+    color_from_json!(jj_conflict_bg, jj, conflict_bg, alert_bg);
+}
+
+impl KubeScheme for CustomTheme {
+    color_from_json!(kube_fg, kube, fg, default_fg);
+    color_from_json!(kube_bg, kube, bg, default_bg);
+    color_from_json!(kube_production_fg, kube, production_fg, alert_fg);
+    color_from_json!(kube_production_bg, kube, production_bg, alert_bg);
+}
+
+impl AwsScheme for CustomTheme {
+    color_from_json!(aws_fg, aws, fg, default_fg);
+    color_from_json!(aws_bg, aws, bg, default_bg);
+    color_from_json!(aws_expired_fg, aws, expired_fg, alert_fg);
+    color_from_json!(aws_expired_bg, aws, expired_bg, alert_bg);
+}
+
+impl GcloudScheme for CustomTheme {
+    color_from_json!(gcloud_fg, gcloud, fg, default_fg);
+    color_from_json!(gcloud_bg, gcloud, bg, default_bg);
+    color_from_json!(gcloud_production_fg, gcloud, production_fg, alert_fg);
+    color_from_json!(gcloud_production_bg, gcloud, production_bg, alert_bg);
+}
+
+impl AzureScheme for CustomTheme {
+    color_from_json!(azure_fg, azure, fg, default_fg);
+    color_from_json!(azure_bg, azure, bg, default_bg);
+    color_from_json!(azure_production_fg, azure, production_fg, alert_fg);
+    color_from_json!(azure_production_bg, azure, production_bg, alert_bg);
+}
+
+impl TerraformScheme for CustomTheme {
+    color_from_json!(terraform_fg, terraform, fg, default_fg);
+    color_from_json!(terraform_bg, terraform, bg, default_bg);
+    color_from_json!(terraform_production_fg, terraform, production_fg, alert_fg);
+    color_from_json!(terraform_production_bg, terraform, production_bg, alert_bg);
+}
+
+impl ContainerScheme for CustomTheme {
+    color_from_json!(container_fg, container, fg, default_fg);
+    color_from_json!(container_bg, container, bg, default_bg);
+}
+
+impl NixScheme for CustomTheme {
+    color_from_json!(nix_fg, nix, fg, default_fg);
+    color_from_json!(nix_bg, nix, bg, default_bg);
+    color_from_json!(nix_pure_fg, nix, pure_fg, nix_fg);
+    color_from_json!(nix_pure_bg, nix, pure_bg, nix_bg);
+    color_from_json!(direnv_loaded_fg, nix, direnv_loaded_fg, default_fg);
+    color_from_json!(direnv_loaded_bg, nix, direnv_loaded_bg, default_bg);
+    color_from_json!(direnv_blocked_fg, nix, direnv_blocked_fg, alert_fg);
+    color_from_json!(direnv_blocked_bg, nix, direnv_blocked_bg, alert_bg);
+}
+
+impl DockerScheme for CustomTheme {
+    color_from_json!(docker_fg, docker, fg, default_fg);
+    color_from_json!(docker_bg, docker, bg, default_bg);
+    color_from_json!(docker_remote_fg, docker, remote_fg, alert_fg);
+    color_from_json!(docker_remote_bg, docker, remote_bg, alert_bg);
+}
+
+impl BatteryScheme for CustomTheme {
+    color_from_json!(battery_fg, battery, fg, default_fg);
+    color_from_json!(battery_bg, battery, bg, default_bg);
+    color_from_json!(battery_low_fg, battery, low_fg, alert_fg);
+    color_from_json!(battery_low_bg, battery, low_bg, alert_bg);
+    color_from_json!(battery_critical_fg, battery, critical_fg, battery_low_fg);
+    color_from_json!(battery_critical_bg, battery, critical_bg, battery_low_bg);
+}
+
+impl LoadScheme for CustomTheme {
+    color_from_json!(load_fg, load, fg, default_fg);
+    color_from_json!(load_bg, load, bg, default_bg);
+}
+
+impl MemoryScheme for CustomTheme {
+    color_from_json!(memory_fg, memory, fg, default_fg);
+    color_from_json!(memory_bg, memory, bg, default_bg);
+}
+
+impl DiskScheme for CustomTheme {
+    color_from_json!(disk_fg, disk, fg, default_fg);
+    color_from_json!(disk_bg, disk, bg, default_bg);
+}
+
+impl JobsScheme for CustomTheme {
+    color_from_json!(jobs_fg, jobs, fg, default_fg);
+    color_from_json!(jobs_bg, jobs, bg, default_bg);
 }
 
 impl PrScheme for CustomTheme {
@@ -232,6 +401,24 @@ impl PrScheme for CustomTheme {
     color_from_json!(pr_status_success_fg, pr, status_success_fg, default_fg);
     color_from_json!(pr_status_failure_fg, pr, status_failure_fg, default_fg);
     color_from_json!(pr_status_pending_fg, pr, status_pending_fg, default_fg);
+    color_from_json!(pr_approved_fg, pr, approved_fg, pr_status_success_fg);
+    color_from_json!(
+        pr_changes_requested_fg,
+        pr,
+        changes_requested_fg,
+        pr_status_failure_fg
+    );
+    color_from_json!(
+        pr_review_required_fg,
+        pr,
+        review_required_fg,
+        pr_status_pending_fg
+    );
+    color_from_json!(pr_threads_fg, pr, threads_fg, default_fg);
+    color_from_json!(pr_conflict_fg, pr, conflict_fg, pr_status_failure_fg);
+    color_from_json!(pr_ci_fg, pr, ci_fg, default_fg);
+    color_from_json!(pr_ci_bg, pr, ci_bg, default_bg);
+    color_from_json!(pr_stale_fg, pr, stale_fg, default_fg);
 
     fn pr_icon() -> &'static str {
         Self::get_str("pr", "icon")
@@ -1,9 +1,12 @@
 use crate::colors::Color;
 use crate::colors::*;
 use crate::modules::{
-    CargoScheme, CmdScheme, CwdScheme, ExitCodeScheme, GitScheme, HostScheme,
-    LastCmdDurationScheme, NvmScheme, PrScheme, PythonEnvScheme, ReadOnlyScheme, SdkmanScheme,
-    ShellScheme, SpacerScheme, TimeScheme, UserScheme,
+    AwsScheme, AzureScheme, BatteryScheme, CargoScheme, CmdScheme, ContainerScheme, CwdScheme,
+    DiskScheme, DockerScheme, ExitCodeScheme, GcloudScheme, GitCommitScheme, GitIdentityScheme,
+    GitScheme, GitTicketScheme, HgScheme, HostScheme, JjScheme, JobsScheme, KubeScheme,
+    LastCmdDurationScheme, LoadScheme, MemoryScheme, NixScheme, NvmScheme, PrScheme,
+    PythonEnvScheme, ReadOnlyScheme, SdkmanScheme, ShellScheme, SpacerScheme, TerraformScheme,
+    TimeScheme, UserScheme,
 };
 use crate::themes::{CompleteTheme, DefaultColors};
 
@@ -18,6 +21,14 @@ impl DefaultColors for RainbowTheme {
     fn default_fg() -> Color {
         green()
     }
+
+    fn alert_bg() -> Color {
+        warning_red()
+    }
+
+    fn alert_fg() -> Color {
+        white()
+    }
 }
 
 impl CompleteTheme for RainbowTheme {}
@@ -172,6 +183,243 @@ impl GitScheme for RainbowTheme {
     fn git_repo_dirty_fg() -> Color {
         white()
     }
+    fn git_submodule_bg() -> Color {
+        dark_blue()
+    }
+    fn git_submodule_fg() -> Color {
+        white()
+    }
+    fn git_worktrees_bg() -> Color {
+        nice_purple()
+    }
+    fn git_worktrees_fg() -> Color {
+        white()
+    }
+}
+
+impl GitCommitScheme for RainbowTheme {
+    fn git_commit_fg() -> Color {
+        white()
+    }
+    fn git_commit_bg() -> Color {
+        turquoise_blue()
+    }
+}
+
+impl GitTicketScheme for RainbowTheme {
+    fn git_ticket_fg() -> Color {
+        white()
+    }
+    fn git_ticket_bg() -> Color {
+        dark_blue()
+    }
+}
+
+impl GitIdentityScheme for RainbowTheme {
+    fn git_identity_fg() -> Color {
+        light_grey()
+    }
+    fn git_identity_bg() -> Color {
+        mid_grey()
+    }
+}
+
+impl HgScheme for RainbowTheme {
+    fn hg_clean_bg() -> Color {
+        blue()
+    }
+    fn hg_clean_fg() -> Color {
+        white()
+    }
+    fn hg_dirty_bg() -> Color {
+        bright_orange()
+    }
+    fn hg_dirty_fg() -> Color {
+        white()
+    }
+    fn hg_modified_bg() -> Color {
+        mid_red()
+    }
+    fn hg_modified_fg() -> Color {
+        white()
+    }
+    fn hg_added_bg() -> Color {
+        forest_green()
+    }
+    fn hg_added_fg() -> Color {
+        white()
+    }
+    fn hg_removed_bg() -> Color {
+        light_red()
+    }
+    fn hg_removed_fg() -> Color {
+        white()
+    }
+    fn hg_unknown_bg() -> Color {
+        warning_red()
+    }
+    fn hg_unknown_fg() -> Color {
+        white()
+    }
+    fn hg_remote_bg() -> Color {
+        mid_grey()
+    }
+    fn hg_remote_fg() -> Color {
+        light_grey()
+    }
+}
+
+impl KubeScheme for RainbowTheme {
+    fn kube_fg() -> Color {
+        white()
+    }
+    fn kube_bg() -> Color {
+        blue()
+    }
+}
+
+impl AwsScheme for RainbowTheme {
+    fn aws_fg() -> Color {
+        black()
+    }
+    fn aws_bg() -> Color {
+        bright_orange()
+    }
+}
+
+impl GcloudScheme for RainbowTheme {
+    fn gcloud_fg() -> Color {
+        black()
+    }
+    fn gcloud_bg() -> Color {
+        light_blue()
+    }
+}
+
+impl AzureScheme for RainbowTheme {
+    fn azure_fg() -> Color {
+        white()
+    }
+    fn azure_bg() -> Color {
+        turquoise_blue()
+    }
+}
+
+impl TerraformScheme for RainbowTheme {
+    fn terraform_fg() -> Color {
+        white()
+    }
+    fn terraform_bg() -> Color {
+        nice_purple()
+    }
+}
+
+impl ContainerScheme for RainbowTheme {
+    fn container_fg() -> Color {
+        black()
+    }
+    fn container_bg() -> Color {
+        light_turquoise()
+    }
+}
+
+impl NixScheme for RainbowTheme {
+    fn nix_fg() -> Color {
+        white()
+    }
+    fn nix_bg() -> Color {
+        dark_blue()
+    }
+    fn nix_pure_bg() -> Color {
+        nice_purple()
+    }
+    fn direnv_loaded_fg() -> Color {
+        black()
+    }
+    fn direnv_loaded_bg() -> Color {
+        light_turquoise()
+    }
+}
+
+impl DockerScheme for RainbowTheme {
+    fn docker_fg() -> Color {
+        white()
+    }
+    fn docker_bg() -> Color {
+        turquoise_blue()
+    }
+}
+
+impl BatteryScheme for RainbowTheme {
+    fn battery_fg() -> Color {
+        black()
+    }
+    fn battery_bg() -> Color {
+        light_grey()
+    }
+    fn battery_low_fg() -> Color {
+        black()
+    }
+    fn battery_low_bg() -> Color {
+        bright_orange()
+    }
+    fn battery_critical_fg() -> Color {
+        white()
+    }
+    fn battery_critical_bg() -> Color {
+        warning_red()
+    }
+}
+
+impl LoadScheme for RainbowTheme {
+    fn load_fg() -> Color {
+        black()
+    }
+    fn load_bg() -> Color {
+        bright_orange()
+    }
+}
+
+impl MemoryScheme for RainbowTheme {
+    fn memory_fg() -> Color {
+        black()
+    }
+    fn memory_bg() -> Color {
+        bright_orange()
+    }
+}
+
+impl DiskScheme for RainbowTheme {
+    fn disk_fg() -> Color {
+        black()
+    }
+    fn disk_bg() -> Color {
+        bright_orange()
+    }
+}
+
+impl JobsScheme for RainbowTheme {
+    fn jobs_fg() -> Color {
+        white()
+    }
+    fn jobs_bg() -> Color {
+        nice_purple()
+    }
+}
+
+impl JjScheme for RainbowTheme {
+    fn jj_clean_bg() -> Color {
+        blue()
+    }
+    fn jj_clean_fg() -> Color {
+        white()
+    }
+    fn jj_changed_bg() -> Color {
+        bright_orange()
+    }
+    fn jj_changed_fg() -> Color {
+        white()
+    }
 }
 
 impl PrScheme for RainbowTheme {
@@ -209,6 +457,15 @@ impl PrScheme for RainbowTheme {
     fn pr_status_pending_fg() -> Color {
         dark_yellow()
     }
+    fn pr_threads_fg() -> Color {
+        white()
+    }
+    fn pr_ci_fg() -> Color {
+        white()
+    }
+    fn pr_ci_bg() -> Color {
+        mid_grey()
+    }
 }
 
 impl ReadOnlyScheme for RainbowTheme {
@@ -1,8 +1,11 @@
 use crate::colors::{black, dark_grey, grey, light_grey, Color};
 use crate::modules::{
-    CargoScheme, CmdScheme, CwdScheme, ExitCodeScheme, GitScheme, HostScheme,
-    LastCmdDurationScheme, NvmScheme, PrScheme, PythonEnvScheme, ReadOnlyScheme, SdkmanScheme,
-    ShellScheme, SpacerScheme, TimeScheme, UserScheme,
+    AwsScheme, AzureScheme, BatteryScheme, CargoScheme, CmdScheme, ContainerScheme, CwdScheme,
+    DiskScheme, DockerScheme, ExitCodeScheme, GcloudScheme, GitCommitScheme, GitIdentityScheme,
+    GitScheme, GitTicketScheme, HgScheme, HostScheme, JjScheme, JobsScheme, KubeScheme,
+    LastCmdDurationScheme, LoadScheme, MemoryScheme, NixScheme, NvmScheme, PrScheme,
+    PythonEnvScheme, ReadOnlyScheme, SdkmanScheme, ShellScheme, SpacerScheme, TerraformScheme,
+    TimeScheme, UserScheme,
 };
 use crate::themes::{CompleteTheme, DefaultColors};
 
@@ -17,6 +20,14 @@ impl DefaultColors for SimpleTheme {
     fn default_fg() -> Color {
         light_grey()
     }
+
+    fn alert_bg() -> Color {
+        Color(161)
+    }
+
+    fn alert_fg() -> Color {
+        Color(15)
+    }
 }
 
 impl CompleteTheme for SimpleTheme {}
@@ -111,6 +122,189 @@ impl TimeScheme for SimpleTheme {
     }
 }
 
+impl HgScheme for SimpleTheme {
+    fn hg_clean_bg() -> Color {
+        Color(148)
+    }
+    fn hg_clean_fg() -> Color {
+        Color(0)
+    }
+    fn hg_dirty_bg() -> Color {
+        Color(161)
+    }
+    fn hg_dirty_fg() -> Color {
+        Color(15)
+    }
+    fn hg_modified_bg() -> Color {
+        Color(130)
+    }
+    fn hg_modified_fg() -> Color {
+        Color(15)
+    }
+    fn hg_added_bg() -> Color {
+        Color(22)
+    }
+    fn hg_added_fg() -> Color {
+        Color(15)
+    }
+    fn hg_removed_bg() -> Color {
+        Color(9)
+    }
+    fn hg_removed_fg() -> Color {
+        Color(15)
+    }
+    fn hg_unknown_bg() -> Color {
+        Color(52)
+    }
+    fn hg_unknown_fg() -> Color {
+        Color(15)
+    }
+    fn hg_remote_bg() -> Color {
+        Color(240)
+    }
+    fn hg_remote_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl KubeScheme for SimpleTheme {
+    fn kube_bg() -> Color {
+        Color(238)
+    }
+    fn kube_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl AwsScheme for SimpleTheme {
+    fn aws_bg() -> Color {
+        Color(238)
+    }
+    fn aws_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl GcloudScheme for SimpleTheme {
+    fn gcloud_bg() -> Color {
+        Color(238)
+    }
+    fn gcloud_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl AzureScheme for SimpleTheme {
+    fn azure_bg() -> Color {
+        Color(238)
+    }
+    fn azure_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl TerraformScheme for SimpleTheme {
+    fn terraform_bg() -> Color {
+        Color(238)
+    }
+    fn terraform_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl ContainerScheme for SimpleTheme {
+    fn container_bg() -> Color {
+        Color(238)
+    }
+    fn container_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl NixScheme for SimpleTheme {
+    fn nix_bg() -> Color {
+        Color(238)
+    }
+    fn nix_fg() -> Color {
+        Color(250)
+    }
+    fn direnv_loaded_bg() -> Color {
+        Color(238)
+    }
+    fn direnv_loaded_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl DockerScheme for SimpleTheme {
+    fn docker_bg() -> Color {
+        Color(238)
+    }
+    fn docker_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl BatteryScheme for SimpleTheme {
+    fn battery_bg() -> Color {
+        Color(238)
+    }
+    fn battery_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl LoadScheme for SimpleTheme {
+    fn load_bg() -> Color {
+        Color(238)
+    }
+    fn load_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl MemoryScheme for SimpleTheme {
+    fn memory_bg() -> Color {
+        Color(238)
+    }
+    fn memory_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl DiskScheme for SimpleTheme {
+    fn disk_bg() -> Color {
+        Color(238)
+    }
+    fn disk_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl JobsScheme for SimpleTheme {
+    fn jobs_bg() -> Color {
+        Color(238)
+    }
+    fn jobs_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl JjScheme for SimpleTheme {
+    fn jj_clean_bg() -> Color {
+        Color(25)
+    }
+    fn jj_clean_fg() -> Color {
+        Color(15)
+    }
+    fn jj_changed_bg() -> Color {
+        Color(161)
+    }
+    fn jj_changed_fg() -> Color {
+        Color(15)
+    }
+}
+
 impl PrScheme for SimpleTheme {
     fn pr_draft_bg() -> Color {
         Color(240)
@@ -146,6 +340,42 @@ impl PrScheme for SimpleTheme {
     fn pr_status_pending_fg() -> Color {
         Color(178)
     }
+    fn pr_threads_fg() -> Color {
+        Color(15)
+    }
+    fn pr_ci_fg() -> Color {
+        Color(15)
+    }
+    fn pr_ci_bg() -> Color {
+        Color(238)
+    }
+}
+
+impl GitCommitScheme for SimpleTheme {
+    fn git_commit_bg() -> Color {
+        Color(238)
+    }
+    fn git_commit_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl GitTicketScheme for SimpleTheme {
+    fn git_ticket_bg() -> Color {
+        Color(238)
+    }
+    fn git_ticket_fg() -> Color {
+        Color(250)
+    }
+}
+
+impl GitIdentityScheme for SimpleTheme {
+    fn git_identity_bg() -> Color {
+        Color(238)
+    }
+    fn git_identity_fg() -> Color {
+        Color(250)
+    }
 }
 
 impl GitScheme for SimpleTheme {
@@ -191,4 +421,16 @@ impl GitScheme for SimpleTheme {
     fn git_repo_dirty_fg() -> Color {
         Color(15)
     }
+    fn git_submodule_bg() -> Color {
+        Color(24)
+    }
+    fn git_submodule_fg() -> Color {
+        Color(15)
+    }
+    fn git_worktrees_bg() -> Color {
+        Color(97)
+    }
+    fn git_worktrees_fg() -> Color {
+        Color(15)
+    }
 }
@@ -1,7 +1,236 @@
 use std::env;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub fn is_remote_shell() -> bool {
     env::var_os("SSH_CLIENT").is_some()
         || env::var_os("SSH_TTY").is_some()
         || env::var_os("SSH_CONNECTION").is_some()
 }
+
+/// Shell-style wildcard match used for the user-supplied patterns in the
+/// config: `*` matches any run of characters (including none) and `?` matches
+/// exactly one. Everything else must match literally. Case-sensitive.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and the text index it was tried against, so
+    // a failed literal match can backtrack by letting the star eat one more char.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    // Only trailing stars may be left over once the text is exhausted.
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Looks up `key` in the `[section]` of an INI-style file, as the AWS, gcloud
+/// and Azure CLIs write them. Lines starting with `#` or `;` are comments, and
+/// both `key = value` and `key: value` are accepted.
+pub fn ini_value<'a>(contents: &'a str, section: &str, key: &str) -> Option<&'a str> {
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+            continue;
+        }
+        if in_section {
+            if let Some((k, value)) = line.split_once(['=', ':']) {
+                if k.trim() == key {
+                    return Some(value.trim());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Runs `command` and returns its stdout if it exits successfully within
+/// `timeout`. A command that overruns is killed, so a slow VCS or network
+/// lookup costs the prompt at most `timeout`.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> Option<String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Drain stdout on another thread, so a chatty command can't fill the pipe
+    // and block before it gets the chance to exit.
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+
+    let output = reader.join().ok()?.ok()?;
+    status.success().then_some(output)
+}
+
+/// A fresh, empty directory under the system temp dir for a test's fixtures,
+/// removed again when dropped - including when the test panics.
+#[cfg(test)]
+pub struct TempDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    /// `superline-<name>-<pid>-<n>`, unique across the tests running in
+    /// parallel in this process.
+    pub fn new(name: &str) -> TempDir {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("superline-{}-{}-{}", name, std::process::id(), n));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempDir {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, ini_value, TempDir};
+
+    #[test]
+    fn literal_patterns_match_exactly() {
+        assert!(glob_match("main", "main"));
+        assert!(!glob_match("main", "mainline"));
+        assert!(!glob_match("main", "mai"));
+    }
+
+    #[test]
+    fn star_matches_any_run() {
+        assert!(glob_match("release/*", "release/1.2"));
+        assert!(glob_match("release/*", "release/"));
+        assert!(!glob_match("release/*", "hotfix/1.2"));
+        assert!(glob_match("*@example.com", "me@example.com"));
+        assert!(!glob_match("*@example.com", "me@example.org"));
+        assert!(glob_match("*prod*", "eks-prod-eu"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn star_backtracks_over_repeated_literals() {
+        assert!(glob_match("*a*b", "xaaab"));
+        assert!(glob_match("a*a", "aaa"));
+        assert!(!glob_match("a*a", "ab"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_char() {
+        assert!(glob_match("v?", "v1"));
+        assert!(!glob_match("v?", "v"));
+        assert!(!glob_match("v?", "v12"));
+    }
+
+    #[test]
+    fn reads_ini_values() {
+        let ini = "
+            region = us-east-1
+            [profile dev]
+            # region = commented-out
+            region = eu-west-1
+            sso_session: corp
+            [profile other]
+            output = json
+        ";
+        assert_eq!(ini_value(ini, "profile dev", "region"), Some("eu-west-1"));
+        assert_eq!(ini_value(ini, "profile dev", "sso_session"), Some("corp"));
+        assert_eq!(ini_value(ini, "profile other", "region"), None);
+        assert_eq!(ini_value(ini, "default", "region"), None);
+    }
+
+    #[test]
+    fn temp_dirs_are_unique_and_removed_on_drop() {
+        let (a, b) = (TempDir::new("utils"), TempDir::new("utils"));
+        assert!(a.is_dir() && b.is_dir());
+        assert_ne!(a.to_path_buf(), b.to_path_buf());
+
+        let path = a.to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_commands_are_cut_off() {
+        use super::output_with_timeout;
+        use std::process::Command;
+        use std::time::{Duration, Instant};
+
+        let quick = output_with_timeout(
+            Command::new("sh").args(["-c", "echo hi"]),
+            Duration::from_secs(5),
+        );
+        assert_eq!(quick.as_deref(), Some("hi\n"));
+
+        let started = Instant::now();
+        let slow = output_with_timeout(
+            Command::new("sh").args(["-c", "sleep 5"]),
+            Duration::from_millis(50),
+        );
+        assert_eq!(slow, None);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}